@@ -1,32 +1,43 @@
 use crate::vec2d::Vec2d;
 use std::f64::consts::PI;
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
+/// An angle stored in degree.
+/// Increasing angles rotate from the x axis towards the y axis, see [Angle::vec].
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default)]
 pub struct Angle {
     degree: f64,
 }
 
-const RADIANS_TO_DEGREES: f64 = PI * 180.0;
+const RADIANS_TO_DEGREES: f64 = 180.0 / PI;
 const DEGREES_TO_RADIANS: f64 = PI / 180.0;
 const DEGREE_FULL_CIRCLE: f64 = 360.0;
+const DEGREE_HALF_CIRCLE: f64 = 180.0;
 
 impl Angle {
     pub const fn from_degree(degree: f64) -> Self {
         Self { degree }
     }
 
+    pub fn from_radian(radian: f64) -> Self {
+        Self {
+            degree: radian * RADIANS_TO_DEGREES,
+        }
+    }
+
+    /// The angle in the range [-90, 90] with the sine `sin`
     pub fn from_sin(sin: f64) -> Angle {
         Angle::from_radian(sin.asin())
     }
+    /// The angle in the range [0, 180] with the cosine `cos`
     pub fn from_cos(cos: f64) -> Angle {
         Angle::from_radian(cos.acos())
     }
 
-    pub fn from_radian(radian: f64) -> Self {
-        Self {
-            degree: radian * RADIANS_TO_DEGREES,
-        }
+    /// The angle of the direction the vector points in, in the range (-180, 180].
+    /// The inverse of [Angle::vec], the zero vector has an angle of 0.
+    pub fn from_vec(vec: Vec2d) -> Self {
+        Angle::from_radian(vec.y.atan2(vec.x))
     }
 
     pub const fn degree(self) -> f64 {
@@ -37,16 +48,43 @@ impl Angle {
         self.degree * DEGREES_TO_RADIANS
     }
 
-    pub fn normalize(&self) -> Self {
+    /// The same angle in the range [0, 360)
+    pub fn normalize(self) -> Self {
+        let degree = self.degree.rem_euclid(DEGREE_FULL_CIRCLE);
+        Self {
+            // tiny negative angles are rounded up to a full circle
+            degree: if degree == DEGREE_FULL_CIRCLE {
+                0.0
+            } else {
+                degree
+            },
+        }
+    }
+
+    /// The same angle in the range (-180, 180]
+    pub fn normalize_signed(self) -> Self {
+        let degree = self.normalize().degree;
         Self {
-            degree: if self.degree >= 0.0 {
-                self.degree % DEGREE_FULL_CIRCLE
+            degree: if degree > DEGREE_HALF_CIRCLE {
+                degree - DEGREE_FULL_CIRCLE
             } else {
-                DEGREE_FULL_CIRCLE + (self.degree % DEGREE_FULL_CIRCLE)
+                degree
             },
         }
     }
 
+    /// The shortest rotation from `self` to `to` in the range (-180, 180],
+    /// positive when it's in the direction of increasing angles
+    pub fn difference(self, to: Self) -> Self {
+        (to - self).normalize_signed()
+    }
+
+    /// Interpolates along the shortest rotation from `self` to `to`, `t` = 0 is `self` and `t` = 1 is `to`.
+    /// Interpolating from 350 to 10 degree passes 0 degree instead of 180.
+    pub fn lerp(self, to: Self, t: f64) -> Self {
+        self + self.difference(to) * t
+    }
+
     pub fn cos(self) -> f64 {
         self.radian().cos()
     }
@@ -54,6 +92,7 @@ impl Angle {
         self.radian().sin()
     }
 
+    /// The normalized vector pointing in the direction of the angle
     pub fn vec(&self) -> Vec2d {
         let rad = self.radian();
         Vec2d {
@@ -77,20 +116,174 @@ macro_rules! impl_op {
             }
         }
     };
+    ($operation: tt, $trait:ident($func:ident), $trait_assign:ident($func_assign:ident), $rhs:ty) => {
+        impl $trait<$rhs> for Angle {
+            type Output = Angle;
+            fn $func(self, rhs: $rhs) -> Self::Output {
+                Self { degree: self.degree $operation rhs }
+            }
+        }
+        impl $trait_assign<$rhs> for Angle {
+            fn $func_assign(&mut self, rhs: $rhs) {
+                self.degree = self.degree $operation rhs;
+            }
+        }
+    };
 }
 impl_op!(+, Add(add), AddAssign(add_assign));
 impl_op!(-, Sub(sub), SubAssign(sub_assign));
-impl_op!(*, Mul(mul), MulAssign(mul_assign));
-impl_op!(/, Div(div), DivAssign(div_assign));
+// multiplying two angles makes no sense, angles are only scaled
+impl_op!(*, Mul(mul), MulAssign(mul_assign), f64);
+impl_op!(/, Div(div), DivAssign(div_assign), f64);
+
+impl Neg for Angle {
+    type Output = Angle;
+    fn neg(self) -> Self::Output {
+        Self {
+            degree: -self.degree,
+        }
+    }
+}
 
 #[cfg(test)]
 mod test {
+    use std::f64::consts::PI;
+
     use crate::angle::Angle;
+    use crate::direction::Direction;
+    use crate::test_util::EPSILON;
+    use crate::vec2d::Vec2d;
+
+    fn assert_degree(angle: Angle, degree: f64) {
+        assert!(
+            (angle.degree() - degree).abs() < EPSILON,
+            "{} isn't {degree}",
+            angle.degree()
+        );
+    }
 
     #[test]
     fn test_normalize() {
         assert_eq!(Angle::from_degree(361.0).normalize().degree(), 1.0);
         assert_eq!(Angle::from_degree(-1.0).normalize().degree(), 359.0);
         assert_eq!(Angle::from_degree(1.0).normalize().degree(), 1.0);
+        assert_eq!(Angle::from_degree(360.0).normalize().degree(), 0.0);
+        assert_eq!(Angle::from_degree(-360.0).normalize().degree(), 0.0);
+        assert_eq!(Angle::from_degree(-720.0).normalize().degree(), 0.0);
+        assert_eq!(Angle::from_degree(-1e-20).normalize().degree(), 0.0);
+        assert_eq!(Angle::from_degree(1085.0).normalize().degree(), 5.0);
+    }
+
+    #[test]
+    fn test_normalize_signed() {
+        assert_eq!(Angle::from_degree(180.0).normalize_signed().degree(), 180.0);
+        assert_eq!(
+            Angle::from_degree(-180.0).normalize_signed().degree(),
+            180.0
+        );
+        assert_eq!(
+            Angle::from_degree(181.0).normalize_signed().degree(),
+            -179.0
+        );
+        assert_eq!(Angle::from_degree(-90.0).normalize_signed().degree(), -90.0);
+        assert_eq!(Angle::from_degree(350.0).normalize_signed().degree(), -10.0);
+        assert_eq!(Angle::from_degree(720.0).normalize_signed().degree(), 0.0);
+    }
+
+    #[test]
+    fn test_radian_conversion() {
+        assert_degree(Angle::from_radian(PI), 180.0);
+        assert_degree(Angle::from_radian(PI / 2.0), 90.0);
+        assert_degree(Angle::from_radian(-PI / 4.0), -45.0);
+        assert!((Angle::from_degree(270.0).radian() - PI * 1.5).abs() < EPSILON);
+        for degree in [-450.0, -30.0, 0.0, 12.5, 90.0, 359.0] {
+            assert_degree(
+                Angle::from_radian(Angle::from_degree(degree).radian()),
+                degree,
+            );
+        }
+    }
+
+    #[test]
+    fn test_trigonometry() {
+        assert_degree(Angle::from_sin(0.5), 30.0);
+        assert_degree(Angle::from_sin(-1.0), -90.0);
+        assert_degree(Angle::from_cos(0.5), 60.0);
+        assert_degree(Angle::from_cos(-1.0), 180.0);
+        assert!((Angle::from_degree(30.0).sin() - 0.5).abs() < EPSILON);
+        assert!((Angle::from_degree(60.0).cos() - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_vec_conversion() {
+        assert_degree(Angle::from_vec(Vec2d { x: 0.0, y: 2.0 }), 90.0);
+        assert_degree(Angle::from_vec(Vec2d { x: -1.0, y: 0.0 }), 180.0);
+        assert_degree(Angle::from_vec(Vec2d { x: 1.0, y: -1.0 }), -45.0);
+        assert_degree(Angle::from_vec(Vec2d::default()), 0.0);
+        for degree in [-170.0, -45.0, 0.0, 10.0, 135.0, 180.0] {
+            assert_degree(Angle::from_vec(Angle::from_degree(degree).vec()), degree);
+        }
+        assert!(Angle::from_degree(90.0)
+            .vec()
+            .approx_eq(Vec2d { x: 0.0, y: 1.0 }, EPSILON));
+    }
+
+    #[test]
+    fn test_direction_angles() {
+        for direction in [
+            Direction::North,
+            Direction::South,
+            Direction::East,
+            Direction::West,
+        ] {
+            assert!(direction.angle().vec().approx_eq(direction.vec(), EPSILON));
+        }
+    }
+
+    #[test]
+    fn test_difference() {
+        let difference = |from: f64, to: f64| {
+            Angle::from_degree(from)
+                .difference(Angle::from_degree(to))
+                .degree()
+        };
+        assert_eq!(difference(10.0, 30.0), 20.0);
+        assert_eq!(difference(30.0, 10.0), -20.0);
+        assert_eq!(difference(350.0, 10.0), 20.0);
+        assert_eq!(difference(10.0, 350.0), -20.0);
+        assert_eq!(difference(-720.0, 90.0), 90.0);
+        assert_eq!(difference(0.0, 180.0), 180.0);
+        assert_eq!(difference(180.0, 0.0), 180.0);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let lerp = |from: f64, to: f64, t: f64| {
+            Angle::from_degree(from)
+                .lerp(Angle::from_degree(to), t)
+                .normalize()
+                .degree()
+        };
+        assert_eq!(lerp(10.0, 30.0, 0.5), 20.0);
+        assert_eq!(lerp(350.0, 10.0, 0.5), 0.0);
+        assert_eq!(lerp(350.0, 10.0, 0.25), 355.0);
+        assert_eq!(lerp(10.0, 350.0, 0.75), 355.0);
+        assert_eq!(lerp(90.0, 450.0, 0.5), 90.0);
+        assert_eq!(lerp(0.0, 100.0, 1.0), 100.0);
+    }
+
+    #[test]
+    fn test_operators() {
+        let angle = Angle::from_degree(30.0);
+        assert_eq!(angle + Angle::from_degree(20.0), Angle::from_degree(50.0));
+        assert_eq!(angle - Angle::from_degree(40.0), Angle::from_degree(-10.0));
+        assert_eq!(angle * 3.0, Angle::from_degree(90.0));
+        assert_eq!(angle / 2.0, Angle::from_degree(15.0));
+        assert_eq!(-angle, Angle::from_degree(-30.0));
+
+        let mut angle = angle;
+        angle *= 2.0;
+        angle -= Angle::from_degree(5.0);
+        assert_eq!(angle, Angle::from_degree(55.0));
     }
 }
@@ -0,0 +1,106 @@
+use crate::angle::Angle;
+use crate::consts::render::FOV;
+use crate::player::Player;
+use crate::ray::{HitInfo, Ray};
+use crate::vec2d::Vec2d;
+
+/// Projects the world onto the screen.
+///
+/// Rays are spread evenly across the camera plane, which is perpendicular to the direction.
+/// This way straight walls stay straight, unlike when spreading the rays by angle.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Camera {
+    pub pos: Vec2d,
+    /// The normalized view direction
+    pub direction: Vec2d,
+    /// Points from the center to the right edge of the screen, its length is `tan(fov / 2)`
+    pub plane: Vec2d,
+    pub screen_width: f64,
+    pub screen_height: f64,
+    /// How far the horizon is moved down from the middle of the screen in pixels, e.g. by the view bobbing
+    pub horizon_offset: f64,
+}
+
+impl Camera {
+    pub fn new(player: &Player, [screen_width, screen_height]: [f64; 2]) -> Self {
+        let direction = player.direction.vec();
+        let plane_length = (FOV.radian() / 2.0).tan();
+        Self {
+            pos: player.pos,
+            direction,
+            // rotated by 90 degree, which is to the right on the screen
+            plane: direction.perpendicular() * plane_length,
+            screen_width,
+            screen_height,
+            horizon_offset: player.bob_offset() * screen_height,
+        }
+    }
+
+    /// The direction of the ray going through `screen_x`.
+    /// The result isn't normalized, it always reaches the camera plane.
+    pub fn ray_direction(&self, screen_x: f64) -> Vec2d {
+        let camera_x = 2.0 * screen_x / self.screen_width - 1.0;
+        self.direction + self.plane * camera_x
+    }
+
+    pub fn ray(&self, screen_x: f64) -> Ray {
+        let direction = self.ray_direction(screen_x);
+        Ray {
+            pos: self.pos,
+            angle: Angle::from_vec(direction),
+        }
+    }
+
+    /// The distance of the hit to the camera plane instead of the camera position.
+    /// Using the euclidean distance for projecting results in a fisheye effect.
+    pub fn perp_distance<T>(&self, hit_info: &HitInfo<T>) -> f64 {
+        (hit_info.hit - self.pos).dot(self.direction)
+    }
+
+    /// The distance of the screen in pixels so that the horizontal and vertical scale are equal
+    pub fn focal_length(&self) -> f64 {
+        (self.screen_width / 2.0) / self.plane.magnitude()
+    }
+
+    /// The height on the screen of something that is one tile high at `perp_distance`
+    pub fn projected_height(&self, perp_distance: f64) -> f64 {
+        self.focal_length() / perp_distance
+    }
+
+    /// The screen y of the horizon, the camera looks straight ahead so it's the middle of the screen unless it's offset
+    pub fn horizon(&self) -> f64 {
+        self.screen_height / 2.0 + self.horizon_offset
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::camera::Camera;
+    use crate::player::Player;
+    use crate::ray::Raycastable;
+    use crate::vec2d::Vec2d;
+    use crate::world::raycast::RayQuery;
+    use crate::world::tile::Tile::{Air, Wall};
+    use crate::world::World;
+
+    #[test]
+    fn test_straight_wall_has_constant_perp_distance() {
+        let mut rows = vec![[Air; 8]; 8];
+        rows[0] = [Wall; 8];
+        let world = World::from_rows(&rows).unwrap();
+        let player = Player::new(Vec2d { x: 4.0, y: 4.5 }, Angle::from_degree(270.0));
+        let camera = Camera::new(&player, [100.0, 80.0]);
+
+        for column in 0..100 {
+            let hit_info = world.cast_ray(camera.ray(column as f64 + 0.5)).unwrap();
+            let perp_distance = camera.perp_distance(&hit_info);
+            assert!((perp_distance - 3.5).abs() < 1e-3, "{perp_distance}");
+
+            let direction = camera.ray_direction(column as f64 + 0.5);
+            let hit_info = world.cast(&RayQuery::new(camera.pos, direction)).unwrap();
+            assert!((hit_info.perp_distance - 3.5).abs() < 1e-9);
+        }
+        assert_eq!(camera.projected_height(2.0), camera.focal_length() / 2.0);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::color::Color;
+
+/// A rectangle `[x, y, width, height]` in pixels
+pub type Rect = [f64; 4];
+
+/// Where the renderer draws to.
+///
+/// The scene is made of filled rectangles and a few lines, so a backend only needs to draw those.
+/// Coordinates are in pixels with the origin at the top left, colors are blended using their alpha.
+pub trait Canvas {
+    /// The width and height in pixels
+    fn size(&self) -> [f64; 2];
+
+    /// Fills every pixel whose center is inside the rectangle
+    fn fill_rect(&mut self, rect: Rect, color: Color);
+
+    /// Draws a line `[x1, y1, x2, y2]` which is `width` pixels wide
+    fn line(&mut self, line: [f64; 4], width: f64, color: Color);
+}
+
+/// Draws into a piston [Graphics](graphics::Graphics) backend like OpenGL
+#[cfg(feature = "graphics")]
+pub struct GraphicsCanvas<'a, G> {
+    g: &'a mut G,
+    ctx: graphics::Context,
+}
+
+#[cfg(feature = "graphics")]
+impl<'a, G> GraphicsCanvas<'a, G>
+where
+    G: graphics::Graphics,
+{
+    pub fn new(g: &'a mut G, ctx: graphics::Context) -> Self {
+        Self { g, ctx }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<G> Canvas for GraphicsCanvas<'_, G>
+where
+    G: graphics::Graphics,
+{
+    fn size(&self) -> [f64; 2] {
+        self.ctx.get_view_size()
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        self.g.rectangle(
+            &graphics::Rectangle::new(color),
+            rect,
+            &self.ctx.draw_state,
+            self.ctx.transform,
+        );
+    }
+
+    fn line(&mut self, line: [f64; 4], width: f64, color: Color) {
+        self.g.line(
+            &graphics::Line::new(color, width / 2.0),
+            line,
+            &self.ctx.draw_state,
+            self.ctx.transform,
+        );
+    }
+}
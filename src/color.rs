@@ -1,6 +1,15 @@
 use std::ops::{Add, Div, Mul, Sub};
 
-use graphics::types::{Color, ColorComponent};
+/// A color component in the range [0, 1]
+pub type ColorComponent = f32;
+/// A RGBA color
+pub type Color = [ColorComponent; 4];
+
+pub const BLACK: Color = [0.0, 0.0, 0.0, 1.0];
+pub const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+pub const RED: Color = [1.0, 0.0, 0.0, 1.0];
+pub const GREEN: Color = [0.0, 0.5, 0.0, 1.0];
+pub const BLUE: Color = [0.0, 0.0, 1.0, 1.0];
 
 macro_rules! impl_do {
     ($fn_name:ident, $fn_name_other:ident, $fn_name_single:ident : $op:tt) => {
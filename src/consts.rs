@@ -5,18 +5,13 @@ pub mod window {
 
 pub mod render {
     use crate::angle::Angle;
-    use crate::consts::window::WINDOW_SIZE;
+    use crate::color::Color;
 
-    // TODO: Find good values
+    /// The horizontal field of view
     pub const FOV: Angle = Angle::from_degree(90.0);
-    pub const CHANGE_ANGLE_PER_RAY: Angle = Angle::from_degree(0.25);
-    // TODO: dynamically calculate these values in World::render
-    pub const RAY_SCREEN_WIDTH: f64 =
-        WINDOW_SIZE[0] / (FOV.degree() / CHANGE_ANGLE_PER_RAY.degree());
-    pub const RAY_SCREEN_HEIGHT: f64 = WINDOW_SIZE[1];
-    pub const CHANGE_SCREEN_X_PER_RAY: f64 = RAY_SCREEN_WIDTH;
-
-    pub const NEAR_PLANE: f64 = 0.1;
+    /// The color of the ceiling when there is no ceiling texture
+    pub const CEILING_COLOR: Color = [0.25, 0.25, 0.3, 1.0];
+
     pub const FAR_PLANE: f64 = 25.0;
 
     pub const FLOOR_HEIGHT: f64 = 0.3;
@@ -28,15 +23,74 @@ pub mod render {
 pub mod player {
     use crate::angle::Angle;
 
-    pub const MOVE_SPEED: f64 = 0.01;
-    pub const TURN_SPEED: Angle = Angle::from_degree(2.5);
+    /// The top speed when walking, in tiles per second
+    pub const MOVE_SPEED: f64 = 2.5;
+    /// Per second
+    pub const TURN_SPEED: Angle = Angle::from_degree(300.0);
+    /// How much faster the player moves while running
+    pub const RUN_MULTIPLIER: f64 = 1.8;
+    /// How many times the top speed is gained per second while moving, so it's reached in `1 / ACCELERATION` seconds
+    pub const ACCELERATION: f64 = 10.0;
+    /// How much of its speed the player loses per second
+    pub const FRICTION: f64 = 6.0;
+    /// Slower speeds lose as much as this speed to friction, so the player comes to a stop instead of creeping along
+    pub const STOP_SPEED: f64 = 0.8;
+    /// How far the view bobs up and down at [MOVE_SPEED], relative to the screen height
+    pub const BOB_AMOUNT: f64 = 0.015;
+    /// How many times the view bobs up and down per tile walked
+    pub const BOB_FREQUENCY: f64 = 0.8;
+    /// How far away a door can be opened
+    pub const INTERACT_DISTANCE: f64 = 1.5;
+    /// The radius of the circle the player collides with the world as, in tiles.
+    /// It keeps the camera far enough away from walls that they aren't clipped.
+    pub const RADIUS: f64 = 0.25;
+}
+
+pub mod world {
+    /// How far a door opens per second, it takes `1 / DOOR_SPEED` seconds to open it
+    pub const DOOR_SPEED: f64 = 1.2;
+}
+
+pub mod enemy {
+    use crate::angle::Angle;
+
+    /// How far away an enemy can see the player, in tiles
+    pub const VIEW_DISTANCE: f64 = 10.0;
+    /// The angle of the cone in front of an enemy in which it can see the player
+    pub const VIEW_CONE: Angle = Angle::from_degree(100.0);
+    /// In tiles per second
+    pub const PATROL_SPEED: f64 = 0.8;
+    /// In tiles per second
+    pub const CHASE_SPEED: f64 = 1.1;
+    /// How many seconds an enemy keeps chasing after it last saw the player
+    pub const LOSE_TRACK_TIME: f64 = 4.0;
+    /// How often the path to the player is searched again while chasing, in seconds
+    pub const REPATH_TIME: f64 = 0.5;
+    /// How close an enemy has to get to a waypoint to reach it, in tiles
+    pub const ARRIVE_DISTANCE: f64 = 0.1;
+    pub const HEALTH: f64 = 100.0;
 }
 
-pub mod controls {
-    use piston::Key;
+pub mod weapon {
+    use crate::angle::Angle;
+    use crate::weapon::WeaponStats;
+
+    pub const PISTOL: WeaponStats = WeaponStats {
+        sprite: "pistol",
+        fire_rate: 3.0,
+        range: 20.0,
+        spread: Angle::from_degree(2.0),
+        damage: 25.0,
+    };
+    /// How long the firing animation of the weapon sprite takes, in seconds
+    pub const FIRE_ANIMATION_TIME: f64 = 0.25;
+    /// The height of the weapon sprite relative to the screen height
+    pub const WEAPON_SCREEN_SIZE: f64 = 0.5;
+}
 
-    pub const FORWARD: Key = Key::W;
-    pub const BACKWARD: Key = Key::S;
-    pub const TURN_RIGHT: Key = Key::D;
-    pub const TURN_LEFT: Key = Key::A;
+pub mod game {
+    /// The length of a single simulation step in seconds
+    pub const TIME_STEP: f64 = 1.0 / 120.0;
+    /// The most simulation steps per update, when the game falls further behind the rest is dropped
+    pub const MAX_STEPS_PER_UPDATE: u32 = 10;
 }
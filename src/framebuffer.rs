@@ -0,0 +1,175 @@
+use std::path::Path;
+
+use image::{ImageResult, Rgba, RgbaImage};
+
+use crate::canvas::{Canvas, Rect};
+use crate::color::Color;
+use crate::player::Player;
+use crate::world::World;
+
+/// A RGBA8 pixel buffer in memory which can be drawn into without a window or a GPU
+#[derive(Clone, PartialEq, Debug)]
+pub struct Framebuffer {
+    image: RgbaImage,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: RgbaImage::new(width, height),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        self.image.get_pixel_checked(x, y).map(|pixel| pixel.0)
+    }
+
+    /// Draws a color over the pixel at x, y using alpha blending
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+        let Some(pixel) = self.image.get_pixel_mut_checked(x, y) else {
+            return;
+        };
+        let alpha = color[3].clamp(0.0, 1.0);
+        let [r, g, b, a] = pixel.0.map(|c| c as f32 / 255.0);
+        let blended = [
+            color[0] * alpha + r * (1.0 - alpha),
+            color[1] * alpha + g * (1.0 - alpha),
+            color[2] * alpha + b * (1.0 - alpha),
+            alpha + a * (1.0 - alpha),
+        ];
+        *pixel = to_rgba(blended);
+    }
+
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> ImageResult<()> {
+        self.image.save_with_format(path, image::ImageFormat::Png)
+    }
+}
+
+fn to_rgba(color: Color) -> Rgba<u8> {
+    Rgba(color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+}
+
+/// The range of pixels whose centers are in `[start, start + length)`, clamped to `[0, max)`
+fn pixel_range(start: f64, length: f64, max: u32) -> std::ops::Range<u32> {
+    let first = (start - 0.5).ceil().clamp(0.0, max as f64) as u32;
+    let last = (start + length - 0.5).ceil().clamp(0.0, max as f64) as u32;
+    first..last
+}
+
+impl Canvas for Framebuffer {
+    fn size(&self) -> [f64; 2] {
+        [self.width() as f64, self.height() as f64]
+    }
+
+    fn fill_rect(&mut self, [x, y, width, height]: Rect, color: Color) {
+        let columns = pixel_range(x, width, self.width());
+        for row in pixel_range(y, height, self.height()) {
+            for column in columns.clone() {
+                self.blend_pixel(column, row, color);
+            }
+        }
+    }
+
+    fn line(&mut self, [x1, y1, x2, y2]: [f64; 4], width: f64, color: Color) {
+        let radius = width / 2.0;
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length_squared = dx * dx + dy * dy;
+        if length_squared == 0.0 {
+            return;
+        }
+        let columns = pixel_range(x1.min(x2) - radius, dx.abs() + width, self.width());
+        for row in pixel_range(y1.min(y2) - radius, dy.abs() + width, self.height()) {
+            for column in columns.clone() {
+                let (px, py) = (column as f64 + 0.5 - x1, row as f64 + 0.5 - y1);
+                // how far along the line the pixel is and how far away from it
+                let along = (px * dx + py * dy) / length_squared;
+                let distance = (px * dy - py * dx).abs() / length_squared.sqrt();
+                if (0.0..=1.0).contains(&along) && distance <= radius {
+                    self.blend_pixel(column, row, color);
+                }
+            }
+        }
+    }
+}
+
+/// Renders what the player sees into a framebuffer of the given size, without needing a window
+fn render_framebuffer(world: &World, player: &Player, width: u32, height: u32) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(width, height);
+    world.render_scene(&mut framebuffer, player);
+    framebuffer
+}
+
+/// Renders what the player sees into an image of the given size, see [render_to_image] to write it to a file
+pub fn render_image(world: &World, player: &Player, width: u32, height: u32) -> RgbaImage {
+    render_framebuffer(world, player, width, height).into_image()
+}
+
+/// Renders what the player sees into an image of the given size and writes it to `path` as a PNG
+pub fn render_to_image(
+    world: &World,
+    player: &Player,
+    width: u32,
+    height: u32,
+    path: impl AsRef<Path>,
+) -> ImageResult<()> {
+    render_framebuffer(world, player, width, height).save_png(path)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::canvas::Canvas;
+    use crate::framebuffer::{render_image, render_to_image, Framebuffer};
+    use crate::player::Player;
+    use crate::world::World;
+
+    #[test]
+    fn test_rectangle_covers_exact_pixels() {
+        let mut framebuffer = Framebuffer::new(8, 8);
+        framebuffer.fill_rect([2.0, 3.0, 1.0, 4.0], [1.0, 0.0, 0.0, 1.0]);
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let expected = if x == 2 && (3..7).contains(&y) {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 0]
+                };
+                assert_eq!(framebuffer.pixel(x, y), Some(expected), "{x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_alpha_blending() {
+        let mut framebuffer = Framebuffer::new(2, 2);
+        framebuffer.fill_rect([0.0, 0.0, 2.0, 2.0], [0.0, 0.0, 1.0, 1.0]);
+        framebuffer.fill_rect([0.0, 0.0, 2.0, 2.0], [1.0, 0.0, 0.0, 0.5]);
+        assert_eq!(framebuffer.pixel(1, 1), Some([128, 0, 128, 255]));
+    }
+
+    #[test]
+    fn test_render_to_image_writes_png() {
+        let world = World::default();
+        let player = Player::default();
+        let path = std::env::temp_dir().join("raytrace_rs_render_to_image.png");
+        render_to_image(&world, &player, 16, 8, &path).unwrap();
+
+        let written = image::open(&path).unwrap().into_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written, render_image(&world, &player, 16, 8));
+    }
+}
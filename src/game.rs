@@ -1,59 +1,75 @@
-use crate::angle::Angle;
-use crate::consts::window::WINDOW_SIZE;
+use crate::canvas::Canvas;
+use crate::consts::game::{MAX_STEPS_PER_UPDATE, TIME_STEP};
+use crate::input::{Action, Controls, Input};
 use crate::player::Player;
-use crate::vec2d::Vec2d;
 use crate::world::World;
-use glutin_window::OpenGL;
-use graphics::color::BLUE;
-use graphics::{Context, Graphics, Rectangle};
-use opengl_graphics::GlGraphics;
-use piston::{ButtonArgs, RenderArgs, UpdateArgs};
+use piston_input::{ButtonArgs, UpdateArgs};
 
+/// The state of a running game, it's up to the frontend to feed it events and render it
+#[derive(Debug, Default)]
 pub struct Game {
-    graphics: GlGraphics,
     pub player: Player,
     pub world: World,
+    pub input: Input,
+    /// The time in seconds which hasn't been simulated yet
+    accumulator: f64,
 }
 
 impl Game {
-    pub fn new(opengl: OpenGL) -> Self {
-        Self {
-            graphics: GlGraphics::new(opengl),
-            player: Default::default(),
-            world: World::default(),
-        }
+    pub fn new() -> Self {
+        Self::default()
     }
-    pub fn from_world(opengl: OpenGL, player_pos: Vec2d, world: World) -> Self {
+    pub fn from_world(world: World, controls: Controls) -> Self {
+        let spawn = world.spawn();
         Self {
-            graphics: GlGraphics::new(opengl),
-            player: Player::new(player_pos, Angle::from_degree(0.0)),
+            player: Player::new(spawn.pos, spawn.direction),
             world,
+            input: Input::new(controls),
+            accumulator: 0.0,
         }
     }
 
-    pub fn render(&mut self, args: &RenderArgs) {
-        self.graphics
-            .draw(args.viewport(), |mut ctx: Context, g: &mut GlGraphics| {
-                // Clear screen with blue
-                g.rectangle(
-                    &Rectangle::new(BLUE),
-                    [0.0, 0.0, WINDOW_SIZE[0], WINDOW_SIZE[1]],
-                    &ctx.draw_state,
-                    ctx.transform,
-                );
-                // render world
-                self.world.render(g, &mut ctx, &self.player);
-                // render minimap
-                self.world.render_mini_map(g, &mut ctx, &self.player);
-            });
+    pub fn render<C>(&self, canvas: &mut C)
+    where
+        C: Canvas,
+    {
+        self.world.render_scene(canvas, &self.player);
     }
 
+    /// Runs the simulation in steps of [TIME_STEP] seconds, independent of how often this is called
     pub fn update(&mut self, args: &UpdateArgs) {
-        self.player.update(&self.world);
-        self.world.update();
+        self.accumulator += args.dt;
+        let mut steps = 0;
+        while self.accumulator >= TIME_STEP {
+            if steps == MAX_STEPS_PER_UPDATE {
+                self.accumulator = 0.0;
+                break;
+            }
+            self.step(TIME_STEP);
+            self.accumulator -= TIME_STEP;
+            steps += 1;
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        self.player.update(&self.world, &self.input, dt);
+        if self.input.just_pressed(Action::Interact) {
+            self.player.interact(&mut self.world);
+        }
+        // holding fire keeps shooting as fast as the weapon allows
+        if self.input.is_down(Action::Fire) {
+            self.player.fire(&mut self.world);
+        }
+        self.world.update(dt, &self.player);
+        self.input.end_step();
     }
 
     pub fn button(&mut self, args: &ButtonArgs) {
-        self.player.button(args);
+        self.input.button(args);
+    }
+
+    /// Handles relative mouse motion in pixels
+    pub fn mouse_relative(&mut self, motion: [f64; 2]) {
+        self.input.mouse_relative(motion);
     }
 }
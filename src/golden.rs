@@ -0,0 +1,317 @@
+//! Golden image tests for the renderer.
+//!
+//! Every test renders a fixed scene offscreen and compares it to `tests/golden/<name>.png`.
+//! When a comparison fails the rendered image and a diff image are written to `target/golden/`.
+//! Run the tests with `UPDATE_GOLDEN=1` to regenerate the reference images.
+
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+
+use crate::canvas::Canvas;
+use crate::color::BLACK;
+use crate::consts::weapon::FIRE_ANIMATION_TIME;
+use crate::consts::world::DOOR_SPEED;
+use crate::framebuffer::{render_image, Framebuffer};
+use crate::player::Player;
+use crate::texture::{Texture, Textures};
+use crate::world::tile::Tile;
+use crate::world::World;
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+/// The maximum difference of a single color channel before a pixel counts as different
+const TOLERANCE: u8 = 2;
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn reference_path(name: &str) -> PathBuf {
+    manifest_dir()
+        .join("tests/golden")
+        .join(name)
+        .with_extension("png")
+}
+
+fn output_path(name: &str) -> PathBuf {
+    manifest_dir()
+        .join("target/golden")
+        .join(name)
+        .with_extension("png")
+}
+
+/// Marks every pixel which differs by more than [TOLERANCE] in red and returns the amount of them
+fn diff(actual: &RgbaImage, expected: &RgbaImage) -> (usize, RgbaImage) {
+    let mut different = 0;
+    let diff = RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let a = actual.get_pixel(x, y);
+        let e = expected.get_pixel(x, y);
+        if a.0.iter().zip(e.0).any(|(a, e)| a.abs_diff(e) > TOLERANCE) {
+            different += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            // a dimmed version of the image so it's clear where the differences are
+            Rgba([a[0] / 4, a[1] / 4, a[2] / 4, 255])
+        }
+    });
+    (different, diff)
+}
+
+/// Parses the map with its entities and uses the textures from `assets/textures`
+fn load_world(map: &str) -> World {
+    let mut world = World::parse_map(map).expect("test map is valid");
+    world.spawn_entities();
+    world.set_textures(
+        Textures::load_dir(manifest_dir().join("assets/textures")).expect("textures are valid"),
+    );
+    world
+}
+
+fn spawn_player(world: &World) -> Player {
+    let spawn = world.spawn();
+    Player::new(spawn.pos, spawn.direction)
+}
+
+/// Renders the world as seen from its spawn and compares it with the reference image.
+/// The weapon isn't drawn, so changes to it only show up in [golden_weapon].
+fn assert_golden(name: &str, world: &World) {
+    let player = spawn_player(world);
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.fill_rect([0.0, 0.0, WIDTH as f64, HEIGHT as f64], BLACK);
+    world.render(&mut framebuffer, &player);
+    world.render_mini_map(&mut framebuffer, &player);
+    assert_image(name, &framebuffer.into_image());
+}
+
+/// Compares the image with the reference image, or replaces the reference image with `UPDATE_GOLDEN=1`
+fn assert_image(name: &str, actual: &RgbaImage) {
+    let reference = reference_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(reference.parent().unwrap()).unwrap();
+        actual.save(&reference).unwrap();
+        return;
+    }
+
+    let expected = match image::open(&reference) {
+        Ok(expected) => expected.into_rgba8(),
+        Err(err) => panic!(
+            "unable to open {}: {err}, run the tests with UPDATE_GOLDEN=1 to create it",
+            reference.display()
+        ),
+    };
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "{name}: the reference image has a different size"
+    );
+
+    let (different, diff) = diff(actual, &expected);
+    if different > 0 {
+        let output = output_path(name);
+        std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+        actual.save(&output).unwrap();
+        let diff_output = output.with_file_name(format!("{name}.diff.png"));
+        diff.save(&diff_output).unwrap();
+        panic!(
+            "{name}: {different} pixels differ from {}, see {} and {}",
+            reference.display(),
+            output.display(),
+            diff_output.display()
+        );
+    }
+}
+
+#[test]
+fn golden_corridor() {
+    assert_golden(
+        "corridor",
+        &load_world(
+            "spawn 1.5 2.5 0
+---
+##########
+#........#
+#........#
+#........#
+##########
+",
+        ),
+    );
+}
+
+#[test]
+fn golden_corner() {
+    assert_golden(
+        "corner",
+        &load_world(
+            "spawn 1.5 1.5 45
+---
+SSSSSS
+S....S
+S....S
+S....S
+SSSSSS
+",
+        ),
+    );
+}
+
+#[test]
+fn golden_mixed_walls() {
+    assert_golden(
+        "mixed_walls",
+        &load_world(
+            "spawn 3.5 4.5 250
+---
+BBBBBBB
+B.....#
+B.S...#
+B.....#
+B.....#
+#######
+",
+        ),
+    );
+}
+
+#[test]
+fn golden_close_wall() {
+    assert_golden(
+        "close_wall",
+        &load_world(
+            "spawn 1.1 1.5 180
+---
+###
+#.#
+###
+",
+        ),
+    );
+}
+
+#[test]
+fn golden_textured_floor() {
+    let mut world = load_world(
+        "spawn 1.5 3.5 300
+---
+#######
+#.....#
+#.....#
+#.....#
+#######
+",
+    );
+    let mut textures = Textures::default();
+    let checker = Texture::from_fn(8, 8, |x, y| {
+        if (x < 4) == (y < 4) {
+            [0.8, 0.8, 0.8, 1.0]
+        } else {
+            [0.3, 0.2, 0.1, 1.0]
+        }
+    })
+    .unwrap();
+    textures.set_floor(Tile::Air, checker);
+    textures.set_ceiling(Texture::from_fn(2, 2, |x, _| {
+        [0.1, 0.1, 0.2 + x as f32 * 0.3, 1.0]
+    }));
+    world.set_textures(textures);
+    assert_golden("textured_floor", &world);
+}
+
+#[test]
+fn golden_sprites() {
+    let mut world = load_world(
+        "spawn 1.5 2.5 0
+entity barrel 3.5 2.0
+entity barrel 6.5 3.0
+entity arrow 4.5 3.2
+entity arrow 9.5 2.5
+---
+###########
+#.........#
+#...#.....#
+#.........#
+###########
+",
+    );
+    // one color per frame so it's visible which frame was picked
+    let frames = [
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [1.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 1.0, 1.0],
+    ]
+    .map(|color| {
+        Texture::from_fn(8, 8, |x, y| {
+            if (2..6).contains(&x) && y >= 2 {
+                color
+            } else {
+                [0.0; 4]
+            }
+        })
+        .unwrap()
+    });
+    let mut textures = Textures::load_dir(manifest_dir().join("assets/textures")).unwrap();
+    textures.set_sprite("arrow", frames.to_vec());
+    world.set_textures(textures);
+    assert_golden("sprites", &world);
+}
+
+#[test]
+fn golden_doors() {
+    let mut world = load_world(
+        "spawn 1.5 1.5 45
+---
+#########
+#...#...#
+#...D...#
+#...#...#
+##D######
+#.......#
+#########
+",
+    );
+    // the door on the left is closed, the one on the right half open
+    world.door_mut(2, 4).unwrap().toggle();
+    world.update(0.5 / DOOR_SPEED, &Player::default());
+    assert_golden("doors", &world);
+}
+
+#[test]
+fn golden_windows() {
+    assert_golden(
+        "windows",
+        &load_world(
+            "spawn 1.5 3.5 10
+entity barrel 5.5 4.5
+---
+##########
+#........#
+#...G....#
+#...G.B..#
+#...H....#
+#...H....#
+##########
+",
+        ),
+    );
+}
+
+#[test]
+fn golden_weapon() {
+    let mut world = load_world(
+        "spawn 1.5 2.5 0
+---
+##########
+#........#
+#........#
+#........#
+##########
+",
+    );
+    // halfway through the firing animation
+    let mut player = spawn_player(&world);
+    player.fire(&mut world).unwrap();
+    player.weapon.update(FIRE_ANIMATION_TIME * 0.5);
+    assert_image("weapon", &render_image(&world, &player, WIDTH, HEIGHT));
+}
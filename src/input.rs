@@ -0,0 +1,307 @@
+//! Maps keys, mouse buttons and mouse motion onto the actions of the game.
+//!
+//! The bindings are read from a TOML file, every action that isn't listed keeps its default bindings:
+//! ```toml
+//! # degree turned per pixel the mouse moved
+//! mouse_sensitivity = 0.15
+//!
+//! [bindings]
+//! move_forward = [{ key = "W" }, { key = "Up" }]
+//! fire = [{ mouse = "Left" }]
+//! turn_right = [{ mouse_motion = "Right" }]
+//! ```
+//! Keys and mouse buttons use the names of [Key] and [MouseButton].
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use piston_input::{Button, ButtonArgs, ButtonState, Key, MouseButton};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Interact,
+    Run,
+    Fire,
+}
+
+/// A direction the mouse can be moved in
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum MouseMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    /// Relative mouse motion, it doesn't hold an action down but gives it an amount, see [Input::motion]
+    MouseMotion(MouseMotion),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Controls {
+    /// Degree turned per pixel the mouse moved
+    pub mouse_sensitivity: f64,
+    pub bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        let key = Binding::Key;
+        let bindings = [
+            (Action::MoveForward, vec![key(Key::W), key(Key::Up)]),
+            (Action::MoveBackward, vec![key(Key::S), key(Key::Down)]),
+            (Action::StrafeLeft, vec![key(Key::A)]),
+            (Action::StrafeRight, vec![key(Key::D)]),
+            (
+                Action::TurnLeft,
+                vec![key(Key::Left), Binding::MouseMotion(MouseMotion::Left)],
+            ),
+            (
+                Action::TurnRight,
+                vec![key(Key::Right), Binding::MouseMotion(MouseMotion::Right)],
+            ),
+            (Action::Interact, vec![key(Key::E)]),
+            (Action::Run, vec![key(Key::LShift)]),
+            (
+                Action::Fire,
+                vec![key(Key::Space), Binding::Mouse(MouseButton::Left)],
+            ),
+        ];
+        Self {
+            mouse_sensitivity: 0.15,
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+/// The layout of a controls file, everything is optional
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ControlsFile {
+    mouse_sensitivity: Option<f64>,
+    #[serde(default)]
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+#[derive(Debug)]
+pub enum ControlsError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+}
+
+impl Display for ControlsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlsError::Io(err) => write!(f, "{err}"),
+            ControlsError::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for ControlsError {}
+
+impl From<io::Error> for ControlsError {
+    fn from(err: io::Error) -> Self {
+        ControlsError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ControlsError {
+    fn from(err: toml::de::Error) -> Self {
+        ControlsError::Parse(err)
+    }
+}
+
+impl Controls {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ControlsError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a controls file, see the [module documentation](self) for the format
+    pub fn parse(source: &str) -> Result<Self, ControlsError> {
+        let file: ControlsFile = toml::from_str(source)?;
+        let mut controls = Self::default();
+        if let Some(mouse_sensitivity) = file.mouse_sensitivity {
+            controls.mouse_sensitivity = mouse_sensitivity;
+        }
+        controls.bindings.extend(file.bindings);
+        Ok(controls)
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// The state of the actions, fed by the window events
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    controls: Controls,
+    /// The keys and mouse buttons which are held down
+    pressed: HashSet<Binding>,
+    /// The bindings pressed since the last [Input::end_step]
+    just_pressed: HashSet<Binding>,
+    /// The mouse motion since the last [Input::end_step] in pixels
+    motion: HashMap<MouseMotion, f64>,
+}
+
+impl Input {
+    pub fn new(controls: Controls) -> Self {
+        Self {
+            controls,
+            ..Self::default()
+        }
+    }
+
+    pub fn controls(&self) -> &Controls {
+        &self.controls
+    }
+
+    pub fn button(&mut self, args: &ButtonArgs) {
+        let binding = match args.button {
+            Button::Keyboard(key) => Binding::Key(key),
+            Button::Mouse(button) => Binding::Mouse(button),
+            _ => return,
+        };
+        match args.state {
+            ButtonState::Press => {
+                // key repeat sends presses for keys which are already held down
+                if self.pressed.insert(binding) {
+                    self.just_pressed.insert(binding);
+                }
+            }
+            ButtonState::Release => {
+                self.pressed.remove(&binding);
+            }
+        }
+    }
+
+    pub fn mouse_relative(&mut self, [dx, dy]: [f64; 2]) {
+        let mut add = |motion, amount: f64| {
+            if amount > 0.0 {
+                *self.motion.entry(motion).or_default() += amount;
+            }
+        };
+        add(MouseMotion::Right, dx);
+        add(MouseMotion::Left, -dx);
+        add(MouseMotion::Down, dy);
+        add(MouseMotion::Up, -dy);
+    }
+
+    /// Whether a key or mouse button bound to the action is held down
+    pub fn is_down(&self, action: Action) -> bool {
+        self.controls
+            .bindings(action)
+            .iter()
+            .any(|binding| self.pressed.contains(binding))
+    }
+
+    /// Whether a key or mouse button bound to the action was pressed since the last step
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.controls
+            .bindings(action)
+            .iter()
+            .any(|binding| self.just_pressed.contains(binding))
+    }
+
+    /// The mouse motion bound to the action since the last step, scaled by the mouse sensitivity
+    pub fn motion(&self, action: Action) -> f64 {
+        let pixels: f64 = self
+            .controls
+            .bindings(action)
+            .iter()
+            .filter_map(|binding| match binding {
+                Binding::MouseMotion(motion) => self.motion.get(motion),
+                _ => None,
+            })
+            .sum();
+        pixels * self.controls.mouse_sensitivity
+    }
+
+    /// Forgets the presses and the mouse motion once a simulation step used them
+    pub fn end_step(&mut self) {
+        self.just_pressed.clear();
+        self.motion.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::{Action, Binding, Controls, Input};
+    use piston_input::{Button, ButtonArgs, ButtonState, Key};
+
+    fn key(key: Key, state: ButtonState) -> ButtonArgs {
+        ButtonArgs {
+            state,
+            button: Button::Keyboard(key),
+            scancode: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_controls() {
+        let controls = Controls::parse(
+            r#"
+            mouse_sensitivity = 0.5
+            [bindings]
+            move_forward = [{ key = "I" }]
+            fire = [{ mouse = "Right" }, { mouse_motion = "Up" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(controls.mouse_sensitivity, 0.5);
+        assert_eq!(
+            controls.bindings(Action::MoveForward),
+            [Binding::Key(Key::I)]
+        );
+        // actions missing in the file keep their default bindings
+        assert_eq!(
+            controls.bindings(Action::Interact),
+            Controls::default().bindings(Action::Interact)
+        );
+
+        assert!(Controls::parse("[bindings]\njump = [{ key = \"Space\" }]").is_err());
+        assert!(Controls::parse("[bindings]\nfire = [{ key = \"NoKey\" }]").is_err());
+    }
+
+    #[test]
+    fn test_default_controls_file() {
+        let source = include_str!("../controls.toml");
+        assert_eq!(Controls::parse(source).unwrap(), Controls::default());
+    }
+
+    #[test]
+    fn test_input_actions() {
+        let mut input = Input::new(Controls::default());
+        input.button(&key(Key::W, ButtonState::Press));
+        input.button(&key(Key::Up, ButtonState::Press));
+        input.button(&key(Key::W, ButtonState::Release));
+        assert!(input.is_down(Action::MoveForward));
+        assert!(input.just_pressed(Action::MoveForward));
+        input.mouse_relative([10.0, 3.0]);
+        assert_eq!(input.motion(Action::TurnRight), 10.0 * 0.15);
+        assert_eq!(input.motion(Action::TurnLeft), 0.0);
+
+        input.end_step();
+        assert!(input.is_down(Action::MoveForward));
+        assert!(!input.just_pressed(Action::MoveForward));
+        assert_eq!(input.motion(Action::TurnRight), 0.0);
+    }
+}
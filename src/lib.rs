@@ -0,0 +1,28 @@
+//! A raycasting engine.
+//!
+//! The world, the raycasting and the software renderer don't need a window.
+//! The scene is drawn into a [Canvas](canvas::Canvas), which the backends implement.
+//! The `graphics` feature adds a canvas drawing with piston2d-graphics
+//! and the `window` feature adds what's needed to run the game in a window with OpenGL.
+
+pub mod angle;
+pub mod camera;
+pub mod canvas;
+pub mod color;
+pub mod consts;
+pub mod direction;
+pub mod framebuffer;
+pub mod game;
+pub mod input;
+pub mod player;
+pub mod ray;
+pub mod terminal;
+pub mod texture;
+pub mod vec2d;
+pub mod weapon;
+pub mod world;
+
+#[cfg(test)]
+mod golden;
+#[cfg(test)]
+mod test_util;
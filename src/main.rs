@@ -1,30 +1,130 @@
-extern crate glutin_window;
-extern crate graphics;
-extern crate opengl_graphics;
-extern crate piston;
-extern crate slab;
-
-use crate::consts::window::{WINDOW_NAME, WINDOW_SIZE};
-use crate::game::Game;
-use crate::vec2d::Vec2d;
-use crate::world::tile::Tile;
-use crate::world::tile::Tile::{Air, Wall};
-use crate::world::World;
-use glutin_window::GlutinWindow;
-use opengl_graphics::OpenGL;
-use piston::{ButtonEvent, EventSettings, Events, RenderEvent, UpdateEvent, WindowSettings};
-
-pub mod angle;
-pub mod color;
-pub mod consts;
-pub mod direction;
-pub mod game;
-pub mod player;
-pub mod ray;
-pub mod vec2d;
-pub mod world;
+use std::io::IsTerminal;
+
+use raytrace_rs::consts::window::WINDOW_SIZE;
+use raytrace_rs::framebuffer::render_to_image;
+use raytrace_rs::game::Game;
+use raytrace_rs::input::Controls;
+use raytrace_rs::player::Player;
+use raytrace_rs::terminal::render_frame;
+use raytrace_rs::texture::Textures;
+use raytrace_rs::world::World;
+
+const DEFAULT_MAP: &str = "maps/test.map";
+const TEXTURE_DIR: &str = "assets/textures";
+/// Loaded when it exists, otherwise the default controls are used
+const CONTROLS_PATH: &str = "controls.toml";
 
 fn main() {
+    // usage: raytrace-rs [map] [--screenshot <png>] [--terminal] [--no-view-bob]
+    let mut map_path = None;
+    let mut screenshot_path = None;
+    let mut terminal = false;
+    let mut view_bob = true;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--screenshot" {
+            screenshot_path = args.next();
+        } else if arg == "--terminal" {
+            terminal = true;
+        } else if arg == "--no-view-bob" {
+            view_bob = false;
+        } else {
+            map_path = Some(arg);
+        }
+    }
+    let map_path = map_path.as_deref().unwrap_or(DEFAULT_MAP);
+    let mut world = match World::load(map_path) {
+        Ok(world) => world,
+        Err(err) => {
+            eprintln!("Unable to load map {map_path}: {err}");
+            std::process::exit(1);
+        }
+    };
+    world.spawn_entities();
+    match Textures::load_dir(TEXTURE_DIR) {
+        Ok(textures) => world.set_textures(textures),
+        Err(err) => {
+            eprintln!("Unable to load textures from {TEXTURE_DIR}: {err}");
+            std::process::exit(1);
+        }
+    }
+
+    // render a single frame without opening a window
+    if let Some(screenshot_path) = screenshot_path {
+        let spawn = world.spawn();
+        let player = Player::new(spawn.pos, spawn.direction);
+        let [width, height] = WINDOW_SIZE.map(|size| size as u32);
+        if let Err(err) = render_to_image(&world, &player, width, height, &screenshot_path) {
+            eprintln!("Unable to save screenshot {screenshot_path}: {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut game = Game::from_world(world, load_controls());
+    game.player.view_bob = view_bob;
+    if terminal {
+        run_terminal(game);
+        return;
+    }
+
+    #[cfg(feature = "window")]
+    run_window(game);
+    #[cfg(not(feature = "window"))]
+    {
+        eprintln!(
+            "Built without the window feature, only --screenshot and --terminal are available"
+        );
+        std::process::exit(1);
+    }
+}
+
+fn run_terminal(game: Game) {
+    // print a single frame when the output isn't shown in a terminal, e.g. in a CI log
+    if !std::io::stdout().is_terminal() {
+        println!("{}", render_frame(&game, 80, 24));
+        return;
+    }
+
+    #[cfg(feature = "terminal")]
+    {
+        let mut game = game;
+        if let Err(err) = raytrace_rs::terminal::run(&mut game) {
+            eprintln!("Unable to run in the terminal: {err}");
+            std::process::exit(1);
+        }
+    }
+    #[cfg(not(feature = "terminal"))]
+    {
+        eprintln!("Built without the terminal feature, the terminal can only show a single frame");
+        std::process::exit(1);
+    }
+}
+
+fn load_controls() -> Controls {
+    if !std::path::Path::new(CONTROLS_PATH).exists() {
+        return Controls::default();
+    }
+    match Controls::load(CONTROLS_PATH) {
+        Ok(controls) => controls,
+        Err(err) => {
+            eprintln!("Unable to load controls from {CONTROLS_PATH}: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "window")]
+fn run_window(mut game: Game) {
+    use glutin_window::GlutinWindow;
+    use opengl_graphics::{GlGraphics, OpenGL};
+    use piston::{
+        AdvancedWindow, ButtonEvent, EventSettings, Events, MouseRelativeEvent, RenderEvent,
+        UpdateEvent, WindowSettings,
+    };
+    use raytrace_rs::canvas::GraphicsCanvas;
+    use raytrace_rs::consts::window::WINDOW_NAME;
+
     let opengl = OpenGL::V3_2;
 
     let mut window: GlutinWindow = WindowSettings::new(WINDOW_NAME, WINDOW_SIZE)
@@ -32,17 +132,17 @@ fn main() {
         .resizable(false)
         .build()
         .expect("Unable to build window");
+    // keeps the cursor in the window for mouse look
+    window.set_capture_cursor(true);
 
-    let mut game = Game::from_world(
-        opengl,
-        Vec2d { x: 5.0, y: 5.0 },
-        World::from_tiles(TEST_WORLD),
-    );
+    let mut graphics = GlGraphics::new(opengl);
 
     let mut events = Events::new(EventSettings::new());
     while let Some(event) = events.next(&mut window) {
         if let Some(args) = event.render_args() {
-            game.render(&args);
+            graphics.draw(args.viewport(), |ctx, g| {
+                game.render(&mut GraphicsCanvas::new(g, ctx))
+            });
         }
 
         if let Some(args) = event.update_args() {
@@ -52,18 +152,9 @@ fn main() {
         if let Some(args) = event.button_args() {
             game.button(&args);
         }
+
+        if let Some(motion) = event.mouse_relative_args() {
+            game.mouse_relative(motion);
+        }
     }
 }
-
-const TEST_WORLD: [[Tile; 10]; 10] = [
-    [Wall, Wall, Wall, Wall, Wall, Wall, Wall, Wall, Wall, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Wall, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Air, Air, Air, Air, Air, Air, Air, Air, Wall],
-    [Wall, Wall, Wall, Wall, Wall, Wall, Wall, Wall, Wall, Wall],
-];
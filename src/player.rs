@@ -1,85 +1,220 @@
+use std::f64::consts::TAU;
+
 use crate::angle::Angle;
-use crate::consts::controls::*;
-use crate::consts::player::{MOVE_SPEED, TURN_SPEED};
+use crate::consts::player::{
+    ACCELERATION, BOB_AMOUNT, BOB_FREQUENCY, FRICTION, INTERACT_DISTANCE, MOVE_SPEED, RADIUS,
+    RUN_MULTIPLIER, STOP_SPEED, TURN_SPEED,
+};
+use crate::input::{Action, Input};
 use crate::vec2d::Vec2d;
-use crate::world::tile::Tile;
+use crate::weapon::{Shot, Weapon};
 use crate::world::World;
-use piston::{Button, ButtonArgs, ButtonState};
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Player {
     pub pos: Vec2d,
     pub direction: Angle,
-    pressed_buttons: [bool; 4],
+    /// In tiles per second
+    pub velocity: Vec2d,
+    /// The radius of the circle the player collides with the world as, see [World::move_circle]
+    pub radius: f64,
+    /// Whether the view bobs up and down while moving, see [Player::bob_offset]
+    pub view_bob: bool,
+    /// How far a bob up and down has progressed, in radians
+    bob_phase: f64,
+    pub weapon: Weapon,
 }
 
-const BTN_FORWARD: usize = 0;
-const BTN_BACKWARD: usize = 1;
-const BTN_TURN_RIGHT: usize = 2;
-const BTN_TURN_LEFT: usize = 3;
-
 impl Player {
     pub fn new(pos: Vec2d, direction: Angle) -> Self {
         Self {
             pos,
             direction,
-            pressed_buttons: [false; 4],
+            velocity: Vec2d::default(),
+            radius: RADIUS,
+            view_bob: true,
+            bob_phase: 0.0,
+            weapon: Weapon::default(),
         }
     }
 
-    pub fn button(&mut self, args: &ButtonArgs) {
-        let press = args.state == ButtonState::Press;
-        match args.button {
-            Button::Keyboard(FORWARD) => self.pressed_buttons[BTN_FORWARD] = press,
-            Button::Keyboard(BACKWARD) => self.pressed_buttons[BTN_BACKWARD] = press,
-            Button::Keyboard(TURN_RIGHT) => self.pressed_buttons[BTN_TURN_RIGHT] = press,
-            Button::Keyboard(TURN_LEFT) => self.pressed_buttons[BTN_TURN_LEFT] = press,
-            _ => {}
+    /// Moves and turns the player for `dt` seconds according to the actions of the input
+    pub fn update(&mut self, world: &World, input: &Input, dt: f64) {
+        let axis = |positive, negative| {
+            input.is_down(positive) as i32 as f64 - input.is_down(negative) as i32 as f64
+        };
+        let forward = self.direction.vec();
+        // rotated by 90 degree, which is to the right on the screen
+        let right = forward.perpendicular();
+        // moving diagonally isn't faster
+        let wish = (forward * axis(Action::MoveForward, Action::MoveBackward)
+            + right * axis(Action::StrafeRight, Action::StrafeLeft))
+        .normalize();
+        let max_speed = if input.is_down(Action::Run) {
+            MOVE_SPEED * RUN_MULTIPLIER
+        } else {
+            MOVE_SPEED
+        };
+        self.accelerate(wish, max_speed, dt);
+
+        // slides along walls, a player stuck in a wall is pushed out
+        let prev_pos = self.pos;
+        self.pos = world.move_circle(self.pos, self.velocity * dt, self.radius);
+        if dt > 0.0 {
+            // the speed into walls is lost, being pushed out of a wall doesn't speed the player up
+            let speed = self.velocity.magnitude();
+            self.velocity = ((self.pos - prev_pos) / dt).clamp_magnitude(speed);
         }
+        let bob_step = self.velocity.magnitude() * dt * BOB_FREQUENCY * TAU;
+        self.bob_phase = (self.bob_phase + bob_step) % TAU;
+
+        let turn = TURN_SPEED * (axis(Action::TurnRight, Action::TurnLeft) * dt)
+            + Angle::from_degree(input.motion(Action::TurnRight) - input.motion(Action::TurnLeft));
+        // kept in [0, 360) so it doesn't grow without bounds when turning in circles
+        self.direction = (self.direction + turn).normalize();
+
+        self.weapon.update(dt);
     }
 
-    pub fn update(&mut self, world: &World) {
-        let prev_collision = Self::is_colliding(self.pos, world);
+    /// Slows the player down with friction, then speeds them up in the `wish` direction up to `max_speed`
+    fn accelerate(&mut self, wish: Vec2d, max_speed: f64, dt: f64) {
+        let speed = self.velocity.magnitude();
+        let drop = speed.max(STOP_SPEED) * FRICTION * dt;
+        self.velocity = self.velocity.with_magnitude((speed - drop).max(0.0));
 
-        let mut movement = Vec2d::default();
-        if self.pressed_buttons[BTN_FORWARD] {
-            let move_direction = self.direction.vec() * MOVE_SPEED;
-            movement += move_direction;
+        // only the speed missing towards `wish` is added, so the player doesn't get faster than `max_speed`
+        let missing = max_speed - self.velocity.dot(wish);
+        if missing > 0.0 {
+            self.velocity += wish * (ACCELERATION * max_speed * dt).min(missing);
         }
-        if self.pressed_buttons[BTN_BACKWARD] {
-            let move_direction = self.direction.vec() * -MOVE_SPEED;
-            movement += move_direction;
+    }
+
+    /// How far the view is moved down by bobbing, relative to the screen height.
+    /// It grows with the speed, so it's 0 while standing still, and it's always 0 when [Player::view_bob] is off.
+    pub fn bob_offset(&self) -> f64 {
+        if !self.view_bob {
+            return 0.0;
         }
+        self.bob_phase.sin() * BOB_AMOUNT * self.velocity.magnitude() / MOVE_SPEED
+    }
+
+    /// Fires the weapon where the player is looking, see [Weapon::fire]
+    pub fn fire(&mut self, world: &mut World) -> Option<Shot> {
+        self.weapon.fire(self.pos, self.direction, world)
+    }
 
-        // only check collisions when we're not in a solid (to allow the player to escape)
-        if !prev_collision {
-            let collision_only_x = Self::is_colliding(self.pos + movement.with_y(0.0), world);
-            let collision_only_y = Self::is_colliding(self.pos + movement.with_x(0.0), world);
-            let collision_both = Self::is_colliding(self.pos + movement, world);
-
-            if !collision_both {
-                self.pos += movement;
-            } else if !collision_only_x {
-                self.pos += movement.with_y(0.0);
-            } else if !collision_only_y {
-                self.pos += movement.with_x(0.0);
+    /// Opens or closes the door the player is looking at
+    pub fn interact(&self, world: &mut World) {
+        let Some([x, y]) = world.door_in_front(self.pos, self.direction, INTERACT_DISTANCE) else {
+            return;
+        };
+        if let Some(door) = world.door_mut(x, y) {
+            door.toggle();
+        }
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self::new(Vec2d::default(), Angle::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::consts::player::{MOVE_SPEED, RADIUS, RUN_MULTIPLIER};
+    use crate::input::{Controls, Input};
+    use crate::player::Player;
+    use crate::vec2d::Vec2d;
+    use crate::world::tile::Tile::{Air, Wall};
+    use crate::world::World;
+    use piston_input::{Button, ButtonArgs, ButtonState, Key};
+
+    fn press(key: Key) -> Input {
+        let mut input = Input::new(Controls::default());
+        input.button(&ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(key),
+            scancode: None,
+        });
+        input
+    }
+
+    #[test]
+    fn test_movement_is_independent_of_the_step_size() {
+        let world = World::new(10, 10);
+        let input = press(Key::W);
+        let moved = |steps: u32| {
+            let mut player = Player::new(Vec2d { x: 1.5, y: 1.5 }, Angle::from_degree(0.0));
+            for _ in 0..steps {
+                player.update(&world, &input, 1.0 / steps as f64);
             }
-        } else {
-            self.pos += movement;
+            player.pos.x - 1.5
+        };
+        // only getting up to speed differs a little with the step size
+        assert!((moved(120) - moved(480)).abs() < 0.02);
+        assert!(moved(120) < MOVE_SPEED && moved(120) > 0.9 * MOVE_SPEED);
+    }
+
+    #[test]
+    fn test_acceleration_and_friction() {
+        let world = World::new(20, 10);
+        let mut player = Player::new(Vec2d { x: 1.5, y: 1.5 }, Angle::from_degree(0.0));
+        let dt = 1.0 / 120.0;
+        let walk = press(Key::W);
+        player.update(&world, &walk, dt);
+        assert!(player.velocity.x > 0.0 && player.velocity.x < MOVE_SPEED);
+        for _ in 0..60 {
+            player.update(&world, &walk, dt);
         }
+        assert!((player.velocity.magnitude() - MOVE_SPEED).abs() < 1e-9);
 
-        if self.pressed_buttons[BTN_TURN_RIGHT] {
-            self.direction = self.direction + TURN_SPEED;
+        let mut run = walk.clone();
+        run.button(&ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(Key::LShift),
+            scancode: None,
+        });
+        for _ in 0..60 {
+            player.update(&world, &run, dt);
         }
-        if self.pressed_buttons[BTN_TURN_LEFT] {
-            self.direction = self.direction - TURN_SPEED;
+        assert!((player.velocity.magnitude() - MOVE_SPEED * RUN_MULTIPLIER).abs() < 1e-9);
+        assert_ne!(player.bob_offset(), 0.0);
+
+        // slides a bit after letting go
+        let stopped_at = player.pos.x;
+        for _ in 0..60 {
+            player.update(&world, &Input::new(Controls::default()), dt);
         }
+        assert_eq!(player.velocity, Vec2d::default());
+        assert!(player.pos.x > stopped_at);
+        assert_eq!(player.bob_offset(), 0.0);
+    }
+
+    #[test]
+    fn test_wall_collision() {
+        let world = World::from_rows(&[[Air, Air, Wall], [Air, Air, Wall]]).unwrap();
+        let mut player = Player::new(Vec2d { x: 1.5, y: 0.5 }, Angle::from_degree(30.0));
+        player.update(&world, &press(Key::W), 10.0);
+        // slid along the wall and into the corner without getting closer than the radius
+        assert!(player.pos.approx_eq(
+            Vec2d {
+                x: 2.0 - RADIUS,
+                y: 2.0 - RADIUS
+            },
+            1e-9
+        ));
     }
 
-    fn is_colliding(pos: Vec2d, world: &World) -> bool {
-        world
-            .tile_vec(pos)
-            .map(|tile| tile.is_solid())
-            .unwrap_or(true) // when we're out of the map this should be solid
+    #[test]
+    fn test_strafing() {
+        let world = World::new(10, 10);
+        let mut player = Player::new(Vec2d { x: 5.0, y: 5.0 }, Angle::from_degree(0.0));
+        player.update(&world, &press(Key::D), 1.0);
+        // strafing right while looking along the x axis moves along the y axis
+        assert!((player.pos.x - 5.0).abs() < 1e-9);
+        assert!((player.pos.y - (5.0 + MOVE_SPEED)).abs() < 1e-9);
+        assert_eq!(player.direction, Angle::from_degree(0.0));
     }
 }
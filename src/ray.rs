@@ -11,9 +11,25 @@ pub struct Ray {
 #[derive(PartialEq, Debug)]
 pub struct HitInfo<'a, Obj> {
     pub distance: f64,
+    /// The distance in multiples of the direction the ray was cast with.
+    /// For rays cast through a [Camera](crate::camera::Camera) this is the distance to the camera plane.
+    pub perp_distance: f64,
     pub hit: Vec2d,
     pub hit_object: Option<&'a Obj>,
+    /// The cell of the grid which was hit
+    pub cell: [usize; 2],
+    /// The direction the ray moved in when it hit the face
     pub hit_direction: Direction,
+    /// The position along the hit face in the range [0, 1), from left to right when looking at the face.
+    /// This is the U coordinate for texturing the face.
+    pub wall_offset: f64,
+}
+
+impl<Obj> HitInfo<'_, Obj> {
+    /// The normal of the hit face, pointing back towards the ray
+    pub fn normal(&self) -> Vec2d {
+        -self.hit_direction.vec()
+    }
 }
 
 pub trait Raycastable {
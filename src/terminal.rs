@@ -0,0 +1,259 @@
+//! Renders the game into a terminal.
+//!
+//! Every character cell shows two pixels with the upper half block `▀`,
+//! the foreground color is the upper pixel and the background color the lower one.
+//! Colors are written as truecolor ANSI escapes, so the terminal has to support 24 bit colors.
+
+use std::fmt::Write;
+
+use image::RgbaImage;
+
+use crate::canvas::Canvas;
+use crate::color::BLACK;
+use crate::framebuffer::Framebuffer;
+use crate::game::Game;
+
+const UPPER_HALF_BLOCK: char = '▀';
+const RESET: &str = "\x1b[0m";
+
+/// Converts the image into lines of half blocks, two rows of pixels per line.
+/// The lines are separated with `\r\n` so they also line up in raw mode.
+pub fn half_blocks(image: &RgbaImage) -> String {
+    let mut output = String::new();
+    let black = image::Rgba([0, 0, 0, 255]);
+    for y in (0..image.height()).step_by(2) {
+        if y > 0 {
+            output.push_str("\r\n");
+        }
+        let mut colors = None;
+        for x in 0..image.width() {
+            let upper = image.get_pixel(x, y);
+            let lower = image.get_pixel_checked(x, y + 1).unwrap_or(&black);
+            // only change the colors when they differ from the last cell
+            if colors != Some((upper, lower)) {
+                let [ur, ug, ub, _] = upper.0;
+                let [lr, lg, lb, _] = lower.0;
+                write!(output, "\x1b[38;2;{ur};{ug};{ub}m\x1b[48;2;{lr};{lg};{lb}m").unwrap();
+                colors = Some((upper, lower));
+            }
+            output.push(UPPER_HALF_BLOCK);
+        }
+        output.push_str(RESET);
+    }
+    output
+}
+
+/// Renders what the player sees into `columns` by `rows` character cells.
+/// The minimap is left out, it would cover most of the few pixels a terminal has.
+pub fn render_frame(game: &Game, columns: u16, rows: u16) -> String {
+    let mut framebuffer = Framebuffer::new(columns as u32, rows as u32 * 2);
+    let [width, height] = framebuffer.size();
+    framebuffer.fill_rect([0.0, 0.0, width, height], BLACK);
+    game.world.render(&mut framebuffer, &game.player);
+    half_blocks(framebuffer.image())
+}
+
+#[cfg(feature = "terminal")]
+pub use self::interactive::run;
+
+#[cfg(feature = "terminal")]
+mod interactive {
+    use std::collections::HashMap;
+    use std::io::{self, Write};
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    };
+    use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+    use crossterm::{cursor, execute, queue};
+    use piston_input::{Button, ButtonArgs, ButtonState, Key, UpdateArgs};
+
+    use crate::game::Game;
+    use crate::terminal::render_frame;
+
+    const FRAME_TIME: Duration = Duration::from_millis(1000 / 30);
+    /// Most terminals only report key presses, so a key counts as released when it wasn't repeated for a while.
+    /// The first repeat takes longer than the following ones.
+    const FIRST_REPEAT_TIME: Duration = Duration::from_millis(550);
+    const REPEAT_TIME: Duration = Duration::from_millis(100);
+
+    /// Maps a terminal key onto the piston key with the same meaning
+    fn key(code: KeyCode) -> Option<Key> {
+        Some(match code {
+            KeyCode::Char(' ') => Key::Space,
+            KeyCode::Char(c) if c.is_ascii_alphanumeric() => {
+                Key::from(c.to_ascii_lowercase() as u32)
+            }
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Enter => Key::Return,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Backspace => Key::Backspace,
+            _ => return None,
+        })
+    }
+
+    /// A key which is held down since it was last pressed or repeated
+    struct HeldKey {
+        last_press: Instant,
+        repeated: bool,
+    }
+
+    /// Puts the terminal into raw mode on the alternate screen and restores it when it's dropped,
+    /// so the terminal is usable again even when the game panics
+    struct TerminalGuard {
+        /// Whether the terminal reports releasing keys
+        releases: bool,
+    }
+
+    impl TerminalGuard {
+        fn enter() -> io::Result<Self> {
+            terminal::enable_raw_mode()?;
+            let mut guard = Self { releases: false };
+            let mut stdout = io::stdout();
+            execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+            // with this the terminal reports releasing keys, not every terminal supports it
+            if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+                execute!(
+                    stdout,
+                    PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+                )?;
+                guard.releases = true;
+            }
+            Ok(guard)
+        }
+    }
+
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            // there is no way to report errors from here, so the terminal is restored as far as possible
+            let mut stdout = io::stdout();
+            if self.releases {
+                let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+            }
+            let _ = execute!(stdout, cursor::Show, LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    /// Runs the game in the terminal until escape or ctrl+c is pressed
+    pub fn run(game: &mut Game) -> io::Result<()> {
+        let guard = TerminalGuard::enter()?;
+        run_loop(game, &mut io::stdout(), guard.releases)
+    }
+
+    /// Presses or releases the key in the game and keeps track of the held keys
+    fn key_event(game: &mut Game, held: &mut HashMap<Key, HeldKey>, key: Key, kind: KeyEventKind) {
+        let state = match kind {
+            KeyEventKind::Release => {
+                held.remove(&key);
+                ButtonState::Release
+            }
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                let repeated = held.contains_key(&key);
+                let last_press = Instant::now();
+                held.insert(
+                    key,
+                    HeldKey {
+                        last_press,
+                        repeated,
+                    },
+                );
+                ButtonState::Press
+            }
+        };
+        game.button(&ButtonArgs {
+            state,
+            button: Button::Keyboard(key),
+            scancode: None,
+        });
+    }
+
+    fn run_loop(game: &mut Game, stdout: &mut io::Stdout, releases: bool) -> io::Result<()> {
+        let mut held = HashMap::<Key, HeldKey>::new();
+        let mut last_frame = Instant::now();
+        loop {
+            while event::poll(Duration::ZERO)? {
+                let Event::Key(event) = event::read()? else {
+                    continue;
+                };
+                if is_quit(&event) {
+                    return Ok(());
+                }
+                // terminals don't report shift on its own, so left shift is held while it modifies the other keys
+                if event.modifiers.contains(KeyModifiers::SHIFT) {
+                    key_event(game, &mut held, Key::LShift, event.kind);
+                } else if held.contains_key(&Key::LShift) {
+                    key_event(game, &mut held, Key::LShift, KeyEventKind::Release);
+                }
+                if let Some(key) = key(event.code) {
+                    key_event(game, &mut held, key, event.kind);
+                }
+            }
+            if !releases {
+                held.retain(|&key, held_key| {
+                    let timeout = if held_key.repeated {
+                        REPEAT_TIME
+                    } else {
+                        FIRST_REPEAT_TIME
+                    };
+                    let released = held_key.last_press.elapsed() > timeout;
+                    if released {
+                        game.button(&ButtonArgs {
+                            state: ButtonState::Release,
+                            button: Button::Keyboard(key),
+                            scancode: None,
+                        });
+                    }
+                    !released
+                });
+            }
+
+            let now = Instant::now();
+            let dt = (now - last_frame).as_secs_f64();
+            last_frame = now;
+            game.update(&UpdateArgs { dt });
+
+            let (columns, rows) = terminal::size()?;
+            queue!(stdout, cursor::MoveTo(0, 0))?;
+            stdout.write_all(render_frame(game, columns, rows).as_bytes())?;
+            stdout.flush()?;
+
+            if let Some(remaining) = FRAME_TIME.checked_sub(last_frame.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+    }
+
+    fn is_quit(event: &KeyEvent) -> bool {
+        event.code == KeyCode::Esc
+            || (event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::{Rgba, RgbaImage};
+
+    use crate::terminal::half_blocks;
+
+    #[test]
+    fn test_half_blocks() {
+        let mut image = RgbaImage::new(2, 3);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+        image.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        image.put_pixel(1, 1, Rgba([0, 0, 255, 255]));
+        image.put_pixel(0, 2, Rgba([1, 2, 3, 255]));
+
+        assert_eq!(
+            half_blocks(&image),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀▀\x1b[0m\r\n\
+             \x1b[38;2;1;2;3m\x1b[48;2;0;0;0m▀\x1b[38;2;0;0;0m\x1b[48;2;0;0;0m▀\x1b[0m"
+        );
+    }
+}
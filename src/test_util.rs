@@ -0,0 +1,25 @@
+//! Fixtures shared by the unit tests
+
+use crate::vec2d::Vec2d;
+use crate::world::tile::Tile;
+use crate::world::World;
+
+/// The tolerance for comparing floats which should be equal but were calculated in different ways
+pub const EPSILON: f64 = 1e-9;
+
+pub fn pos(x: f64, y: f64) -> Vec2d {
+    Vec2d { x, y }
+}
+
+/// Creates a world from rows of map characters, see [Tile::from_char]
+pub fn parse_rows(rows: &[&str]) -> World {
+    let rows: Vec<Vec<Tile>> = rows
+        .iter()
+        .map(|row| {
+            row.chars()
+                .map(|c| Tile::from_char(c).expect("test rows only contain tiles"))
+                .collect()
+        })
+        .collect();
+    World::from_rows(&rows).expect("test rows have the same length")
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::path::Path;
+
+use image::error::{ParameterError, ParameterErrorKind};
+use image::{ImageError, ImageResult, RgbaImage};
+
+use crate::color::Color;
+use crate::world::tile::Tile;
+
+/// A texture kept in memory so it can be sampled while rendering.
+/// It's at least one pixel wide and high, so every texture coordinate has a pixel.
+#[derive(Clone, PartialEq)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Texture {
+    /// Fails for images without pixels
+    pub fn load(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let image = image::open(path)?.into_rgba8();
+        Self::from_image(&image).ok_or_else(|| {
+            ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            ))
+        })
+    }
+
+    /// Returns None for images without pixels
+    pub fn from_image(image: &RgbaImage) -> Option<Self> {
+        Self::from_fn(image.width(), image.height(), |x, y| {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            [r, g, b, a].map(|c| c as f32 / 255.0)
+        })
+    }
+
+    /// Returns None when the width or the height is zero
+    pub fn from_fn<F>(width: u32, height: u32, mut f: F) -> Option<Self>
+    where
+        F: FnMut(u32, u32) -> Color,
+    {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the pixel at x, y. The coordinates wrap around at the edges.
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let x = x % self.width;
+        let y = y % self.height;
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Converts a texture coordinate in the range [0, 1) into the x coordinate of the pixel column
+    pub fn column(&self, u: f64) -> u32 {
+        ((u * self.width as f64) as u32).min(self.width - 1)
+    }
+
+    /// Copies the part of the texture starting at x, y.
+    /// Returns None when the width or the height is zero.
+    pub fn sub_texture(&self, x: u32, y: u32, width: u32, height: u32) -> Option<Self> {
+        Self::from_fn(width, height, |sub_x, sub_y| {
+            self.pixel(x + sub_x, y + sub_y)
+        })
+    }
+
+    /// Splits a horizontal strip of square frames into the frames.
+    /// A texture which isn't a strip of square frames is a single frame.
+    pub fn frames(&self) -> Vec<Self> {
+        if !self.width.is_multiple_of(self.height) {
+            return vec![self.clone()];
+        }
+        (0..self.width / self.height)
+            .map(|frame| {
+                self.sub_texture(frame * self.height, 0, self.height, self.height)
+                    .expect("the height isn't zero")
+            })
+            .collect()
+    }
+
+    /// Returns the pixel at the texture coordinates, which wrap around outside of [0, 1)
+    pub fn sample(&self, u: f64, v: f64) -> Color {
+        let x = (u * self.width as f64).floor() as i64;
+        let y = (v * self.height as f64).floor() as i64;
+        self.pixel(
+            x.rem_euclid(self.width as i64) as u32,
+            y.rem_euclid(self.height as i64) as u32,
+        )
+    }
+}
+
+impl Debug for Texture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Texture")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The textures used for rendering a [World](crate::world::World).
+/// Tiles, floors and the ceiling without a texture are rendered with a flat color.
+#[derive(Default, Debug)]
+pub struct Textures {
+    tiles: HashMap<Tile, Texture>,
+    floors: HashMap<Tile, Texture>,
+    ceiling: Option<Texture>,
+    sprites: HashMap<String, Vec<Texture>>,
+}
+
+impl Textures {
+    pub const CEILING_NAME: &'static str = "ceiling";
+    pub const SPRITE_DIR: &'static str = "sprites";
+
+    /// Loads `<dir>/<name>.png` for every tile that has a [Tile::texture_name] or a [Tile::floor_texture_name]
+    /// and `<dir>/ceiling.png` for the ceiling.
+    /// Every `<dir>/sprites/<name>.png` is loaded as the frames of the sprite `<name>`, see [Texture::frames].
+    /// Missing files are skipped.
+    pub fn load_dir(dir: impl AsRef<Path>) -> ImageResult<Self> {
+        let dir = dir.as_ref();
+        let load = |name: &str| {
+            let path = dir.join(name).with_extension("png");
+            path.exists().then(|| Texture::load(path)).transpose()
+        };
+
+        let mut textures = Self::default();
+        for tile in Tile::ALL {
+            if let Some(texture) = tile.texture_name().map(load).transpose()?.flatten() {
+                textures.set_tile(tile, texture);
+            }
+            if let Some(texture) = tile.floor_texture_name().map(load).transpose()?.flatten() {
+                textures.set_floor(tile, texture);
+            }
+        }
+        textures.ceiling = load(Self::CEILING_NAME)?;
+
+        let sprite_dir = dir.join(Self::SPRITE_DIR);
+        if sprite_dir.is_dir() {
+            for entry in fs::read_dir(sprite_dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|extension| extension == "png") {
+                    let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                        continue;
+                    };
+                    let name = name.to_string();
+                    textures.set_sprite(name, Texture::load(path)?.frames());
+                }
+            }
+        }
+        Ok(textures)
+    }
+
+    pub fn tile(&self, tile: Tile) -> Option<&Texture> {
+        self.tiles.get(&tile)
+    }
+    pub fn set_tile(&mut self, tile: Tile, texture: Texture) -> Option<Texture> {
+        self.tiles.insert(tile, texture)
+    }
+
+    pub fn floor(&self, tile: Tile) -> Option<&Texture> {
+        self.floors.get(&tile)
+    }
+    pub fn set_floor(&mut self, tile: Tile, texture: Texture) -> Option<Texture> {
+        self.floors.insert(tile, texture)
+    }
+
+    pub fn ceiling(&self) -> Option<&Texture> {
+        self.ceiling.as_ref()
+    }
+    pub fn set_ceiling(&mut self, texture: Option<Texture>) {
+        self.ceiling = texture;
+    }
+
+    /// The frames of a sprite, they go around the entity starting in front of it
+    pub fn sprite(&self, name: &str) -> Option<&[Texture]> {
+        self.sprites
+            .get(name)
+            .map(Vec::as_slice)
+            .filter(|frames| !frames.is_empty())
+    }
+    pub fn set_sprite(&mut self, name: impl Into<String>, frames: Vec<Texture>) {
+        self.sprites.insert(name.into(), frames);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::RgbaImage;
+
+    use crate::texture::Texture;
+
+    #[test]
+    fn test_empty_texture() {
+        assert!(Texture::from_fn(0, 4, |_, _| [1.0; 4]).is_none());
+        assert!(Texture::from_fn(4, 0, |_, _| [1.0; 4]).is_none());
+        assert!(Texture::from_image(&RgbaImage::new(0, 0)).is_none());
+
+        let texture = Texture::from_fn(1, 1, |_, _| [1.0; 4]).unwrap();
+        assert_eq!(texture.column(0.99), 0);
+        assert!(texture.sub_texture(0, 0, 0, 1).is_none());
+    }
+
+    #[test]
+    fn test_frames() {
+        let strip = Texture::from_fn(6, 2, |x, _| [x as f32, 0.0, 0.0, 1.0]).unwrap();
+        let frames = strip.frames();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].pixel(1, 0), [5.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -1,5 +1,7 @@
 use conv::{ApproxFrom, ApproxInto, ValueFrom, ValueInto};
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::angle::Angle;
 
 #[derive(Copy, Clone, Default, PartialEq, PartialOrd, Debug)]
 pub struct Vec2d {
@@ -12,19 +14,88 @@ impl Vec2d {
         (self.x.powi(2) + self.y.powi(2)).sqrt()
     }
 
+    /// Scales the vector to the magnitude, the zero vector stays zero
     pub fn with_magnitude(self, magnitude: f64) -> Self {
-        let current_magnitude = self.magnitude();
-        let scalar = magnitude / current_magnitude;
-        self * scalar
+        self.normalize() * magnitude
     }
 
     pub fn distance_to(self, to: Self) -> f64 {
         (self - to).magnitude()
     }
 
+    /// Scales the vector to a magnitude of 1, the zero vector stays zero instead of turning into NaN
     pub fn normalize(self) -> Self {
+        self.try_normalize().unwrap_or_default()
+    }
+
+    /// Scales the vector to a magnitude of 1, returns None for the zero vector
+    pub fn try_normalize(self) -> Option<Self> {
         let magnitude = self.magnitude();
-        self / magnitude
+        (magnitude > 0.0 && magnitude.is_finite()).then(|| self / magnitude)
+    }
+
+    /// Shortens the vector to `max` when it's longer
+    pub fn clamp_magnitude(self, max: f64) -> Self {
+        if self.magnitude() > max {
+            self.with_magnitude(max)
+        } else {
+            self
+        }
+    }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The perp dot product, positive when `other` is rotated in the direction of increasing angles from `self`
+    pub fn cross(self, other: Self) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Rotates the vector in the direction of increasing angles, like [Angle::vec]
+    pub fn rotate(self, angle: Angle) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        Self {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+
+    /// The vector rotated by 90 degree in the direction of increasing angles
+    pub fn perpendicular(self) -> Self {
+        Self {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+
+    /// The signed angle `self` has to be rotated by to point in the direction of `other`, in the range (-180, 180]
+    pub fn angle_between(self, other: Self) -> Angle {
+        Angle::from_radian(self.cross(other).atan2(self.dot(other)))
+    }
+
+    /// Interpolates linearly, `t` = 0 is `self` and `t` = 1 is `to`
+    pub fn lerp(self, to: Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+
+    /// Mirrors the vector at a surface with the normalized `normal`, like a ball bouncing off a wall
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+
+    /// The part of the vector pointing along `onto`, projecting onto the zero vector gives the zero vector
+    pub fn project(self, onto: Self) -> Self {
+        let length_squared = onto.dot(onto);
+        if length_squared == 0.0 {
+            return Self::default();
+        }
+        onto * (self.dot(onto) / length_squared)
+    }
+
+    /// Whether both components differ by at most `epsilon`
+    pub fn approx_eq(self, other: Self, epsilon: f64) -> bool {
+        (self.x - other.x).abs() <= epsilon && (self.y - other.y).abs() <= epsilon
     }
 
     pub fn scale_x(self, new_x: f64) -> Self {
@@ -118,6 +189,16 @@ impl_op!(*, Mul(mul), MulAssign(mul_assign), f64);
 impl_op!(/, Div(div), DivAssign(div_assign));
 impl_op!(/, Div(div), DivAssign(div_assign), f64);
 
+impl Neg for Vec2d {
+    type Output = Vec2d;
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
 impl From<[f64; 2]> for Vec2d {
     fn from(value: [f64; 2]) -> Self {
         Self {
@@ -127,9 +208,9 @@ impl From<[f64; 2]> for Vec2d {
     }
 }
 
-impl Into<[f64; 2]> for Vec2d {
-    fn into(self) -> [f64; 2] {
-        [self.x, self.y]
+impl From<Vec2d> for [f64; 2] {
+    fn from(value: Vec2d) -> Self {
+        [value.x, value.y]
     }
 }
 
@@ -182,3 +263,84 @@ where
         Ok([self.x.approx_into()?, self.y.approx_into()?])
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::test_util::EPSILON;
+    use crate::vec2d::Vec2d;
+
+    fn vec(x: f64, y: f64) -> Vec2d {
+        Vec2d { x, y }
+    }
+
+    #[test]
+    fn test_normalize_zero() {
+        assert_eq!(vec(3.0, 4.0).normalize(), vec(0.6, 0.8));
+        assert_eq!(vec(3.0, 4.0).with_magnitude(10.0), vec(6.0, 8.0));
+        assert_eq!(Vec2d::default().normalize(), Vec2d::default());
+        assert_eq!(Vec2d::default().with_magnitude(2.0), Vec2d::default());
+        assert_eq!(Vec2d::default().try_normalize(), None);
+        assert_eq!(vec(f64::INFINITY, 0.0).try_normalize(), None);
+    }
+
+    #[test]
+    fn test_clamp_magnitude() {
+        assert_eq!(vec(3.0, 4.0).clamp_magnitude(2.5), vec(1.5, 2.0));
+        assert_eq!(vec(3.0, 4.0).clamp_magnitude(6.0), vec(3.0, 4.0));
+        assert_eq!(Vec2d::default().clamp_magnitude(0.0), Vec2d::default());
+    }
+
+    #[test]
+    fn test_products() {
+        assert_eq!(vec(1.0, 2.0).dot(vec(3.0, -4.0)), -5.0);
+        assert_eq!(vec(1.0, 0.0).cross(vec(0.0, 1.0)), 1.0);
+        assert_eq!(vec(0.0, 1.0).cross(vec(1.0, 0.0)), -1.0);
+        assert_eq!(vec(2.0, 4.0).cross(vec(1.0, 2.0)), 0.0);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let angle = Angle::from_degree(30.0);
+        assert!(vec(1.0, 0.0).rotate(angle).approx_eq(angle.vec(), EPSILON));
+        assert!(vec(2.0, 1.0)
+            .rotate(Angle::from_degree(90.0))
+            .approx_eq(vec(-1.0, 2.0), EPSILON));
+        assert_eq!(vec(2.0, 1.0).perpendicular(), vec(-1.0, 2.0));
+        assert!(vec(2.0, 1.0)
+            .rotate(Angle::from_degree(-180.0))
+            .approx_eq(-vec(2.0, 1.0), EPSILON));
+    }
+
+    #[test]
+    fn test_angle_between() {
+        let between = |a: Vec2d, b: Vec2d| a.angle_between(b).degree();
+        assert!((between(vec(1.0, 0.0), vec(0.0, 2.0)) - 90.0).abs() < EPSILON);
+        assert!((between(vec(1.0, 0.0), vec(0.0, -2.0)) + 90.0).abs() < EPSILON);
+        assert!((between(vec(1.0, 1.0), vec(-1.0, 1.0)) - 90.0).abs() < EPSILON);
+        assert_eq!(between(vec(1.0, 0.0), vec(-1.0, 0.0)), 180.0);
+        assert_eq!(between(vec(1.0, 0.0), vec(3.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_lerp_reflect_project() {
+        assert_eq!(vec(0.0, 2.0).lerp(vec(4.0, -2.0), 0.25), vec(1.0, 1.0));
+        assert_eq!(vec(0.0, 2.0).lerp(vec(4.0, -2.0), 1.0), vec(4.0, -2.0));
+
+        assert_eq!(vec(1.0, -1.0).reflect(vec(0.0, 1.0)), vec(1.0, 1.0));
+        assert_eq!(vec(2.0, 3.0).reflect(vec(-1.0, 0.0)), vec(-2.0, 3.0));
+
+        assert_eq!(vec(2.0, 3.0).project(vec(4.0, 0.0)), vec(2.0, 0.0));
+        assert!(vec(2.0, 0.0)
+            .project(vec(1.0, 1.0))
+            .approx_eq(vec(1.0, 1.0), EPSILON));
+        assert_eq!(vec(2.0, 3.0).project(Vec2d::default()), Vec2d::default());
+    }
+
+    #[test]
+    fn test_neg_and_approx_eq() {
+        assert_eq!(-vec(1.0, -2.0), vec(-1.0, 2.0));
+        assert!(vec(1.0, 2.0).approx_eq(vec(1.0 + 1e-10, 2.0 - 1e-10), EPSILON));
+        assert!(!vec(1.0, 2.0).approx_eq(vec(1.0, 2.1), EPSILON));
+    }
+}
@@ -0,0 +1,227 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use crate::angle::Angle;
+use crate::consts::weapon::{FIRE_ANIMATION_TIME, PISTOL};
+use crate::vec2d::Vec2d;
+use crate::world::raycast::{RayQuery, WorldHit};
+use crate::world::{EntityId, World};
+
+/// What makes a weapon different from the others, see [PISTOL] for an example
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WeaponStats {
+    /// The name of the sprite drawn over the view, see [Textures::sprite](crate::texture::Textures::sprite).
+    /// The first frame is shown while the weapon isn't fired, the others are played after every shot.
+    pub sprite: &'static str,
+    /// Shots per second
+    pub fire_rate: f64,
+    /// How far a shot reaches in tiles
+    pub range: f64,
+    /// Shots go off in a random direction inside of this angle around the aimed direction
+    pub spread: Angle,
+    /// The damage dealt to an entity with [Health](crate::world::entity::Health)
+    pub damage: f64,
+}
+
+/// A hitscan weapon, shots hit instantly whatever is in front of them
+#[derive(Clone, Debug)]
+pub struct Weapon {
+    stats: WeaponStats,
+    /// The seconds until the weapon can be fired again
+    cooldown: f64,
+    /// The seconds since the last shot, None before the first one
+    since_shot: Option<f64>,
+    /// Seeded with a constant, so the spread is the same every time the game runs
+    rng: SmallRng,
+}
+
+/// What a shot hit
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Shot {
+    /// The direction the shot went in after the spread was applied
+    pub direction: Angle,
+    /// Where the shot hit a tile or an entity, None when nothing was in range
+    pub hit: Option<Vec2d>,
+    /// The entity which was hit
+    pub entity: Option<EntityId>,
+    /// Whether the entity which was hit died
+    pub killed: bool,
+}
+
+impl Weapon {
+    pub fn new(stats: WeaponStats) -> Self {
+        Self {
+            stats,
+            cooldown: 0.0,
+            since_shot: None,
+            rng: SmallRng::seed_from_u64(0),
+        }
+    }
+
+    pub fn stats(&self) -> &WeaponStats {
+        &self.stats
+    }
+
+    /// Advances the cooldown and the firing animation by `dt` seconds
+    pub fn update(&mut self, dt: f64) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        if let Some(since_shot) = &mut self.since_shot {
+            *since_shot += dt;
+        }
+    }
+
+    /// Whether enough time has passed since the last shot
+    pub fn can_fire(&self) -> bool {
+        self.cooldown <= 0.0
+    }
+
+    /// Fires a shot from `pos` towards `direction` unless the weapon is cooling down.
+    /// The shot hits the first tile or entity with a collider, the entity is damaged and despawned when it dies,
+    /// see [World::damage_entity].
+    pub fn fire(&mut self, pos: Vec2d, direction: Angle, world: &mut World) -> Option<Shot> {
+        if !self.can_fire() {
+            return None;
+        }
+        self.cooldown = 1.0 / self.stats.fire_rate;
+        self.since_shot = Some(0.0);
+
+        let half_spread = self.stats.spread.degree().abs() / 2.0;
+        let direction = if half_spread > 0.0 {
+            direction + Angle::from_degree(self.rng.gen_range(-half_spread..=half_spread))
+        } else {
+            direction
+        };
+        let query = RayQuery::new(pos, direction.vec()).max_distance(self.stats.range);
+        let (hit, entity) = match world.cast_objects(&query) {
+            Some(WorldHit::Entity(hit)) => (Some(hit.hit), Some(hit.id)),
+            Some(WorldHit::Tile(hit)) => (Some(hit.hit), None),
+            None => (None, None),
+        };
+        let killed = entity.is_some_and(|id| world.damage_entity(id, self.stats.damage));
+        Some(Shot {
+            direction,
+            hit,
+            entity,
+            killed,
+        })
+    }
+
+    /// The frame of the weapon sprite to show out of `frames`.
+    /// After a shot every frame but the first is shown for the same time within [FIRE_ANIMATION_TIME].
+    pub fn animation_frame(&self, frames: usize) -> usize {
+        let Some(since_shot) = self.since_shot else {
+            return 0;
+        };
+        if frames <= 1 || since_shot >= FIRE_ANIMATION_TIME {
+            return 0;
+        }
+        let animation_frames = frames - 1;
+        1 + ((since_shot / FIRE_ANIMATION_TIME * animation_frames as f64) as usize)
+            .min(animation_frames - 1)
+    }
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self::new(PISTOL)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::consts::weapon::{FIRE_ANIMATION_TIME, PISTOL};
+    use crate::vec2d::Vec2d;
+    use crate::weapon::{Weapon, WeaponStats};
+    use crate::world::entity::{Entity, Health};
+    use crate::world::tile::Tile::{Air, Wall};
+    use crate::world::World;
+
+    const RIFLE: WeaponStats = WeaponStats {
+        sprite: "rifle",
+        fire_rate: 10.0,
+        range: 4.0,
+        spread: Angle::from_degree(0.0),
+        damage: 40.0,
+    };
+
+    #[test]
+    fn test_fire_rate() {
+        let mut world = World::new(10, 1);
+        let mut weapon = Weapon::new(RIFLE);
+        let pos = Vec2d { x: 0.5, y: 0.5 };
+        assert!(weapon.fire(pos, Angle::default(), &mut world).is_some());
+        assert!(weapon.fire(pos, Angle::default(), &mut world).is_none());
+        weapon.update(0.05);
+        assert!(!weapon.can_fire());
+        weapon.update(0.05);
+        assert!(weapon.fire(pos, Angle::default(), &mut world).is_some());
+    }
+
+    #[test]
+    fn test_damage_and_range() {
+        let mut row = [Air; 12];
+        row[11] = Wall;
+        let mut world = World::from_rows(&[row]).unwrap();
+        let enemy = world.add_entity(Entity {
+            health: Some(Health::new(50.0)),
+            ..Entity::prop("enemy", Vec2d { x: 8.5, y: 0.5 })
+        });
+        let mut weapon = Weapon::new(RIFLE);
+        let mut fire = |world: &mut World, x: f64| {
+            weapon.update(1.0);
+            weapon
+                .fire(Vec2d { x, y: 0.5 }, Angle::default(), world)
+                .unwrap()
+        };
+
+        // out of range
+        let shot = fire(&mut world, 0.5);
+        assert_eq!((shot.hit, shot.entity), (None, None));
+
+        let shot = fire(&mut world, 4.5);
+        assert!(shot.hit.unwrap().approx_eq(Vec2d { x: 8.2, y: 0.5 }, 1e-9));
+        assert_eq!(shot.entity, Some(enemy));
+        assert!(!shot.killed);
+        assert_eq!(world.entity(enemy).unwrap().health.unwrap().current(), 10.0);
+
+        let shot = fire(&mut world, 4.5);
+        assert!(shot.killed);
+        assert!(world.entity(enemy).is_none());
+
+        // the wall behind it can be hit now
+        let shot = fire(&mut world, 8.0);
+        assert_eq!(shot.hit, Some(Vec2d { x: 11.0, y: 0.5 }));
+        assert_eq!(shot.entity, None);
+    }
+
+    #[test]
+    fn test_spread() {
+        let mut world = World::new(10, 10);
+        let mut weapon = Weapon::new(PISTOL);
+        let aimed = Angle::from_degree(90.0);
+        for _ in 0..100 {
+            weapon.update(1.0);
+            let shot = weapon
+                .fire(Vec2d { x: 5.0, y: 5.0 }, aimed, &mut world)
+                .unwrap();
+            let off = aimed.difference(shot.direction).degree().abs();
+            assert!(off <= PISTOL.spread.degree() / 2.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_animation_frame() {
+        let mut weapon = Weapon::new(RIFLE);
+        assert_eq!(weapon.animation_frame(4), 0);
+        weapon.fire(Vec2d::default(), Angle::default(), &mut World::default());
+        assert_eq!(weapon.animation_frame(4), 1);
+        assert_eq!(weapon.animation_frame(1), 0);
+        weapon.update(FIRE_ANIMATION_TIME * 0.5);
+        assert_eq!(weapon.animation_frame(4), 2);
+        weapon.update(FIRE_ANIMATION_TIME * 0.49);
+        assert_eq!(weapon.animation_frame(4), 3);
+        weapon.update(FIRE_ANIMATION_TIME * 0.01);
+        assert_eq!(weapon.animation_frame(4), 0);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::vec2d::Vec2d;
+use crate::world::tile::Tile;
+use crate::world::World;
+
+/// The longest step a circle moves at once in multiples of its radius, so it can't pass through thin walls
+const MAX_STEP: f64 = 0.5;
+/// The longest step of circles too small for [MAX_STEP]
+const MIN_STEP: f64 = 0.05;
+/// How often the overlaps with the tiles are resolved per step.
+/// Pushing a circle out of one tile can push it into another one in a corner.
+const RESOLVE_ITERATIONS: usize = 4;
+/// Overlaps smaller than this are ignored, they are left over from rounding when a circle is pushed out of a tile
+const TOLERANCE: f64 = 1e-9;
+
+impl World {
+    /// Moves a circle with the radius from `pos` by `movement` and returns where it ends up.
+    ///
+    /// The circle can't overlap solid tiles or the closed part of doors, see [World::is_solid_at].
+    /// When it runs into them it slides along them with the part of the movement which isn't blocked,
+    /// around corners as well. A circle which overlaps a tile already is pushed out of it.
+    /// A movement which isn't finite, e.g. from a NaN delta time, leaves the circle where it is.
+    pub fn move_circle(&self, pos: Vec2d, movement: Vec2d, radius: f64) -> Vec2d {
+        let length = movement.magnitude();
+        if !length.is_finite() {
+            return pos;
+        }
+        let max_step = (radius * MAX_STEP).max(MIN_STEP);
+        let steps = (length / max_step).ceil().max(1.0);
+        let step = movement / steps;
+        let mut pos = pos;
+        for _ in 0..steps as usize {
+            pos = self.push_out_circle(pos + step, radius);
+        }
+        pos
+    }
+
+    /// Whether a circle with the radius around `pos` overlaps a solid tile or the closed part of a door
+    pub fn is_circle_colliding(&self, pos: Vec2d, radius: f64) -> bool {
+        self.blocking_boxes(pos, radius)
+            .any(|(min, max)| push_out_of_box(pos, radius, min, max).is_some())
+    }
+
+    /// Moves a circle out of the tiles it overlaps, the shortest way out of every tile
+    fn push_out_circle(&self, mut pos: Vec2d, radius: f64) -> Vec2d {
+        for _ in 0..RESOLVE_ITERATIONS {
+            let mut pushed = false;
+            let boxes: Vec<_> = self.blocking_boxes(pos, radius).collect();
+            for (min, max) in boxes {
+                if let Some(push) = push_out_of_box(pos, radius, min, max) {
+                    pos += push;
+                    pushed = true;
+                }
+            }
+            if !pushed {
+                break;
+            }
+        }
+        pos
+    }
+
+    /// The boxes blocking movement in the tiles the circle could overlap, as their minimum and maximum corners.
+    /// Everything outside of the world is blocking.
+    fn blocking_boxes(&self, pos: Vec2d, radius: f64) -> impl Iterator<Item = (Vec2d, Vec2d)> + '_ {
+        let min = (pos - radius).floor();
+        let max = (pos + radius).floor();
+        let (min_x, min_y) = (min.x as i64, min.y as i64);
+        let (max_x, max_y) = (max.x as i64, max.y as i64);
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| [x, y]))
+            .filter_map(|[x, y]| {
+                let cell = Vec2d {
+                    x: x as f64,
+                    y: y as f64,
+                };
+                let tile_box = (cell, cell + 1.0);
+                let Some([x, y]) = self.cell_coords([x, y]) else {
+                    return Some(tile_box);
+                };
+                match self.tile(x, y) {
+                    Some(Tile::Door) => {
+                        // only the part of the door which isn't open yet blocks, see [World::is_solid_at]
+                        let open = self.door(x, y).map_or(0.0, |door| door.open_amount());
+                        if open >= 1.0 {
+                            None
+                        } else if self.door_faces_x(x, y) {
+                            Some((cell.with_y(cell.y + open), cell + 1.0))
+                        } else {
+                            Some((cell.with_x(cell.x + open), cell + 1.0))
+                        }
+                    }
+                    Some(tile) if tile.is_solid() => Some(tile_box),
+                    _ => None,
+                }
+            })
+    }
+}
+
+/// The shortest movement which moves the circle out of the box, None when they don't overlap
+fn push_out_of_box(pos: Vec2d, radius: f64, min: Vec2d, max: Vec2d) -> Option<Vec2d> {
+    let closest = Vec2d {
+        x: pos.x.clamp(min.x, max.x),
+        y: pos.y.clamp(min.y, max.y),
+    };
+    let offset = pos - closest;
+    let distance = offset.magnitude();
+    if distance >= radius - TOLERANCE {
+        return None;
+    }
+    if distance > 0.0 {
+        return Some(offset * ((radius - distance) / distance));
+    }
+
+    // the center is inside of the box, leave through the closest side
+    let exits = [
+        Vec2d {
+            x: min.x - radius - pos.x,
+            y: 0.0,
+        },
+        Vec2d {
+            x: max.x + radius - pos.x,
+            y: 0.0,
+        },
+        Vec2d {
+            x: 0.0,
+            y: min.y - radius - pos.y,
+        },
+        Vec2d {
+            x: 0.0,
+            y: max.y + radius - pos.y,
+        },
+    ];
+    exits
+        .into_iter()
+        .min_by(|a, b| a.magnitude().total_cmp(&b.magnitude()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{parse_rows, pos, EPSILON};
+    use crate::vec2d::Vec2d;
+
+    #[test]
+    fn test_slide_along_wall() {
+        let world = parse_rows(&["#####", "#...#", "#...#", "#####"]);
+        // diagonally into the wall on the right, only the movement along the wall is left
+        let end = world.move_circle(pos(2.5, 1.5), pos(2.0, 0.5), 0.25);
+        assert!(end.approx_eq(pos(3.75, 2.0), EPSILON), "{end:?}");
+        assert!(!world.is_circle_colliding(end, 0.25));
+
+        // into the corner
+        let end = world.move_circle(pos(2.5, 2.5), pos(5.0, 5.0), 0.25);
+        assert!(end.approx_eq(pos(3.75, 2.75), EPSILON), "{end:?}");
+    }
+
+    #[test]
+    fn test_slide_around_corner() {
+        let world = parse_rows(&["#####", "#...#", "#.#.#", "#...#", "#####"]);
+        // grazing the corner of the pillar pushes the circle around it instead of stopping it
+        let start = pos(1.5, 1.85);
+        let end = world.move_circle(start, pos(0.5, 0.0), 0.25);
+        assert!(end.x > 1.9 && end.y < 1.85, "{end:?}");
+        assert!(!world.is_circle_colliding(end, 0.25));
+    }
+
+    #[test]
+    fn test_no_tunneling() {
+        let world = parse_rows(&["#####", "#.#.#", "#####"]);
+        let end = world.move_circle(pos(1.5, 1.5), pos(2.0, 0.0), 0.25);
+        assert!(end.approx_eq(pos(1.75, 1.5), EPSILON), "{end:?}");
+    }
+
+    #[test]
+    fn test_push_out() {
+        let world = parse_rows(&["####", "#..#", "####"]);
+        assert!(world.is_circle_colliding(pos(1.1, 1.5), 0.25));
+        let end = world.move_circle(pos(1.1, 1.5), Vec2d::default(), 0.25);
+        assert!(end.approx_eq(pos(1.25, 1.5), EPSILON), "{end:?}");
+    }
+
+    #[test]
+    fn test_invalid_movement() {
+        let world = parse_rows(&["####", "#..#", "####"]);
+        let start = pos(1.5, 1.5);
+        for movement in [
+            pos(f64::NAN, 0.0),
+            pos(0.0, f64::INFINITY),
+            pos(f64::NEG_INFINITY, f64::NAN),
+            // finite, but too long to be measured
+            pos(1e200, 1e200),
+        ] {
+            assert_eq!(world.move_circle(start, movement, 0.25), start);
+        }
+    }
+
+    #[test]
+    fn test_door_collision() {
+        let mut world = parse_rows(&["#####", "#.D.#", "#####"]);
+        assert!(world.door_faces_x(2, 1));
+        let end = world.move_circle(pos(1.5, 1.5), pos(2.0, 0.0), 0.25);
+        assert!(end.approx_eq(pos(1.75, 1.5), EPSILON), "{end:?}");
+
+        world.door_mut(2, 1).unwrap().toggle();
+        world.update(100.0, &Default::default());
+        let end = world.move_circle(pos(1.5, 1.5), pos(2.0, 0.0), 0.25);
+        assert!(end.approx_eq(pos(3.5, 1.5), EPSILON), "{end:?}");
+    }
+}
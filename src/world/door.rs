@@ -0,0 +1,63 @@
+use crate::consts::world::DOOR_SPEED;
+
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum DoorState {
+    #[default]
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+/// The state of a [Tile::Door](crate::world::tile::Tile::Door).
+///
+/// A door is a thin slab in the middle of its tile which slides sideways into the wall when it opens.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct Door {
+    state: DoorState,
+    /// How far the slab has slid into the wall, 0 is closed and 1 is open
+    open_amount: f64,
+}
+
+impl Door {
+    pub fn state(&self) -> DoorState {
+        self.state
+    }
+
+    pub fn open_amount(&self) -> f64 {
+        self.open_amount
+    }
+
+    /// Starts opening a closed door and closing an open one.
+    /// A moving door turns around.
+    pub fn toggle(&mut self) {
+        self.state = match self.state {
+            DoorState::Closed | DoorState::Closing => DoorState::Opening,
+            DoorState::Open | DoorState::Opening => DoorState::Closing,
+        };
+    }
+
+    /// Whether the slab covers the point `along` the door, from 0 at the side it slides into to 1
+    pub fn blocks(&self, along: f64) -> bool {
+        along >= self.open_amount
+    }
+
+    /// Moves the door for `dt` seconds
+    pub fn update(&mut self, dt: f64) {
+        match self.state {
+            DoorState::Opening => {
+                self.open_amount = (self.open_amount + DOOR_SPEED * dt).min(1.0);
+                if self.open_amount == 1.0 {
+                    self.state = DoorState::Open;
+                }
+            }
+            DoorState::Closing => {
+                self.open_amount = (self.open_amount - DOOR_SPEED * dt).max(0.0);
+                if self.open_amount == 0.0 {
+                    self.state = DoorState::Closed;
+                }
+            }
+            DoorState::Closed | DoorState::Open => {}
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::angle::Angle;
+use crate::consts::enemy::{
+    ARRIVE_DISTANCE, CHASE_SPEED, HEALTH, LOSE_TRACK_TIME, PATROL_SPEED, REPATH_TIME, VIEW_CONE,
+    VIEW_DISTANCE,
+};
+use crate::ray::{Ray, Raycastable};
+use crate::vec2d::Vec2d;
+use crate::world::entity::{Ai, Entity, Health, Shape, Sprite, UpdateContext};
+use crate::world::World;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EnemyState {
+    /// Walking from waypoint to waypoint
+    Patrolling,
+    /// Walking towards where the player was last seen
+    Chasing {
+        last_seen: Vec2d,
+        /// The seconds since the player was last seen
+        lost_for: f64,
+    },
+}
+
+/// Patrols its waypoints in a loop until it sees the player, then chases the player until it hasn't seen them
+/// for [LOSE_TRACK_TIME] seconds and goes back to patrolling.
+#[derive(Clone, Debug)]
+pub struct Enemy {
+    waypoints: Vec<Vec2d>,
+    next_waypoint: usize,
+    state: EnemyState,
+    /// The rest of the path to the current target, the next point is the last one
+    path: Vec<Vec2d>,
+    /// The seconds until the path to the player, or to a target which couldn't be reached, is searched again
+    repath_timer: f64,
+}
+
+impl Enemy {
+    /// The kind of [EntityPlacement](crate::world::map::EntityPlacement) which is spawned as an enemy
+    /// and the name of its sprite
+    pub const KIND: &'static str = "enemy";
+
+    /// An enemy without waypoints stands still until it sees the player
+    pub fn new(waypoints: Vec<Vec2d>) -> Self {
+        Self {
+            waypoints,
+            next_waypoint: 0,
+            state: EnemyState::Patrolling,
+            path: Vec::new(),
+            repath_timer: 0.0,
+        }
+    }
+
+    /// An entity with an enemy as its AI, a sprite, a collider and health
+    pub fn entity(pos: Vec2d, waypoints: Vec<Vec2d>) -> Entity {
+        Entity {
+            sprite: Some(Sprite::new(Self::KIND)),
+            collider: Some(Shape::Circle { radius: 0.3 }),
+            health: Some(Health::new(HEALTH)),
+            ai: Some(Box::new(Self::new(waypoints))),
+            ..Entity::new(pos)
+        }
+    }
+
+    pub fn state(&self) -> EnemyState {
+        self.state
+    }
+
+    pub fn waypoints(&self) -> &[Vec2d] {
+        &self.waypoints
+    }
+
+    /// Whether the entity sees `target`: it's at most [VIEW_DISTANCE] away, inside of the [VIEW_CONE] around
+    /// [Entity::facing] and there is no wall in between
+    pub fn can_see(entity: &Entity, world: &World, target: Vec2d) -> bool {
+        let to_target = target - entity.pos;
+        let distance = to_target.magnitude();
+        if distance > VIEW_DISTANCE {
+            return false;
+        }
+        let off_center = entity.facing.vec().angle_between(to_target);
+        if off_center.degree().abs() > VIEW_CONE.degree() / 2.0 {
+            return false;
+        }
+        let ray = Ray {
+            pos: entity.pos,
+            angle: Angle::from_vec(to_target),
+        };
+        world
+            .cast_ray(ray)
+            .is_none_or(|hit| hit.distance >= distance)
+    }
+
+    /// Where the enemy is walking to and how fast
+    fn target(&self) -> Option<(Vec2d, f64)> {
+        match self.state {
+            EnemyState::Patrolling => self
+                .waypoints
+                .get(self.next_waypoint)
+                .map(|&waypoint| (waypoint, PATROL_SPEED)),
+            EnemyState::Chasing { last_seen, .. } => Some((last_seen, CHASE_SPEED)),
+        }
+    }
+
+    fn update_state(&mut self, entity: &Entity, context: &UpdateContext<'_>) {
+        let player = context.player.pos;
+        if Self::can_see(entity, context.world, player) {
+            if self.state == EnemyState::Patrolling {
+                self.path.clear();
+                self.repath_timer = 0.0;
+            }
+            self.state = EnemyState::Chasing {
+                last_seen: player,
+                lost_for: 0.0,
+            };
+        } else if let EnemyState::Chasing { lost_for, .. } = &mut self.state {
+            *lost_for += context.dt;
+            if *lost_for >= LOSE_TRACK_TIME {
+                self.state = EnemyState::Patrolling;
+                self.path.clear();
+                self.repath_timer = 0.0;
+            }
+        }
+    }
+}
+
+impl Ai for Enemy {
+    fn update(&mut self, entity: &mut Entity, context: &mut UpdateContext<'_>) {
+        self.update_state(entity, context);
+        let Some((target, speed)) = self.target() else {
+            return;
+        };
+
+        // the player keeps moving, so the path to them gets old.
+        // Unreachable targets are searched for again at most every REPATH_TIME seconds as well.
+        let chasing = matches!(self.state, EnemyState::Chasing { .. });
+        self.repath_timer -= context.dt;
+        if self.repath_timer <= 0.0 && (self.path.is_empty() || chasing) {
+            self.repath_timer = REPATH_TIME;
+            match context.world.find_path(entity.pos, target) {
+                Some(path) => {
+                    self.path = path;
+                    self.path.reverse();
+                }
+                None => {
+                    self.path.clear();
+                    // the waypoint is skipped
+                    if !chasing {
+                        self.next_waypoint = (self.next_waypoint + 1) % self.waypoints.len();
+                    }
+                }
+            }
+        }
+        // the path is reversed, so its end is the first point
+        let path_end = self.path.first().copied();
+
+        let mut step = speed * context.dt;
+        let mut destination = entity.pos;
+        while let Some(&next) = self.path.last() {
+            let to_next = next - destination;
+            let distance = to_next.magnitude();
+            if distance > 0.0 {
+                entity.facing = Angle::from_vec(to_next);
+            }
+            if distance > step {
+                destination += to_next * (step / distance);
+                break;
+            }
+            destination = next;
+            step -= distance;
+            self.path.pop();
+        }
+        // entities without a collider walk through walls
+        entity.pos = match entity.collider {
+            Some(shape) => {
+                context
+                    .world
+                    .move_circle(entity.pos, destination - entity.pos, shape.radius())
+            }
+            None => destination,
+        };
+
+        // arrived, the path to the next waypoint is searched right away.
+        // When a wall kept it from getting there the path is searched again after REPATH_TIME instead.
+        let arrived = path_end.is_some_and(|end| entity.pos.distance_to(end) <= ARRIVE_DISTANCE);
+        if !chasing && arrived && self.path.is_empty() {
+            self.next_waypoint = (self.next_waypoint + 1) % self.waypoints.len();
+            self.repath_timer = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::consts::enemy::LOSE_TRACK_TIME;
+    use crate::player::Player;
+    use crate::test_util::{parse_rows, pos, EPSILON};
+    use crate::vec2d::Vec2d;
+    use crate::world::enemy::{Enemy, EnemyState};
+    use crate::world::entity::{Ai, Commands, Entity, Shape, UpdateContext};
+    use crate::world::{EntityId, World};
+
+    /// A corridor with a room below it which can only be seen into through the gap on the right
+    const MAP: [&str; 5] = ["#######", "#.....#", "#####.#", "#.....#", "#######"];
+
+    /// Updates the enemy in steps of a tenth of a second
+    fn update(enemy: &mut Enemy, entity: &mut Entity, world: &World, player: Vec2d, seconds: f64) {
+        let player = Player::new(player, Angle::default());
+        let mut commands = Commands::default();
+        for _ in 0..(seconds * 10.0).round() as u32 {
+            let mut context = UpdateContext {
+                dt: 0.1,
+                id: EntityId {
+                    index: 0,
+                    generation: 0,
+                },
+                world,
+                player: &player,
+                commands: &mut commands,
+            };
+            enemy.update(entity, &mut context);
+        }
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn test_can_see() {
+        let world = parse_rows(&MAP);
+        let entity = Entity::new(pos(1.5, 1.5));
+        assert!(Enemy::can_see(&entity, &world, pos(5.5, 1.5)));
+        assert!(Enemy::can_see(&entity, &world, pos(5.5, 2.0)));
+        // behind it
+        assert!(!Enemy::can_see(&entity, &world, pos(1.2, 1.5)));
+        // too far away
+        assert!(!Enemy::can_see(&entity, &world, pos(15.5, 1.5)));
+
+        let entity = Entity {
+            facing: Angle::from_degree(90.0),
+            ..Entity::new(pos(1.5, 1.5))
+        };
+        // behind a wall
+        assert!(!Enemy::can_see(&entity, &world, pos(1.5, 3.5)));
+
+        let entity = Entity {
+            facing: Angle::from_degree(90.0),
+            ..Entity::new(pos(5.5, 1.5))
+        };
+        assert!(Enemy::can_see(&entity, &world, pos(5.5, 3.5)));
+        // outside of the view cone
+        assert!(!Enemy::can_see(&entity, &world, pos(4.5, 1.5)));
+    }
+
+    #[test]
+    fn test_patrol() {
+        let world = parse_rows(&MAP);
+        let mut enemy = Enemy::new(vec![pos(3.5, 1.5), pos(1.5, 1.5)]);
+        let mut entity = Entity::new(pos(1.5, 1.5));
+        let player = pos(1.5, 3.5);
+
+        update(&mut enemy, &mut entity, &world, player, 2.0);
+        assert!(entity.pos.approx_eq(pos(3.1, 1.5), EPSILON));
+        assert_eq!(entity.facing.degree(), 0.0);
+        // turns around at the first waypoint
+        update(&mut enemy, &mut entity, &world, player, 1.0);
+        assert!(entity.pos.x < 3.4);
+        assert!((entity.facing.degree() - 180.0).abs() < EPSILON);
+        assert_eq!(enemy.state(), EnemyState::Patrolling);
+    }
+
+    #[test]
+    fn test_unreachable_waypoint() {
+        let world = parse_rows(&MAP);
+        // the first waypoint is inside of a wall
+        let mut enemy = Enemy::new(vec![pos(0.5, 0.5), pos(3.5, 1.5)]);
+        let mut entity = Entity::new(pos(1.5, 1.5));
+        let player = pos(1.5, 3.5);
+
+        // it's skipped, the path to the next one is only searched for after REPATH_TIME
+        update(&mut enemy, &mut entity, &world, player, 0.3);
+        assert_eq!(enemy.next_waypoint, 1);
+        assert_eq!(entity.pos, pos(1.5, 1.5));
+        update(&mut enemy, &mut entity, &world, player, 1.5);
+        assert!(entity.pos.x > 2.0);
+        assert_eq!(enemy.next_waypoint, 1);
+    }
+
+    #[test]
+    fn test_blocked_waypoint() {
+        let world = parse_rows(&MAP);
+        // the circle can't get closer to the first waypoint than its radius from the wall
+        let mut enemy = Enemy::new(vec![pos(1.1, 1.5), pos(4.5, 1.5)]);
+        let mut entity = Entity {
+            collider: Some(Shape::Circle { radius: 0.3 }),
+            ..Entity::new(pos(3.5, 1.5))
+        };
+        let player = pos(1.5, 3.5);
+
+        update(&mut enemy, &mut entity, &world, player, 4.0);
+        assert!(
+            entity.pos.approx_eq(pos(1.3, 1.5), EPSILON),
+            "{:?}",
+            entity.pos
+        );
+        assert_eq!(enemy.next_waypoint, 0);
+    }
+
+    #[test]
+    fn test_chase_and_lose_track() {
+        let world = parse_rows(&MAP);
+        let mut enemy = Enemy::new(Vec::new());
+        let mut entity = Entity::new(pos(1.5, 1.5));
+
+        update(&mut enemy, &mut entity, &world, pos(4.5, 1.5), 0.1);
+        assert_eq!(
+            enemy.state(),
+            EnemyState::Chasing {
+                last_seen: pos(4.5, 1.5),
+                lost_for: 0.0
+            }
+        );
+
+        // the player hid in the room, so the enemy goes to where it saw them last
+        let hidden = pos(1.5, 3.5);
+        update(&mut enemy, &mut entity, &world, hidden, 3.0);
+        assert!(entity.pos.approx_eq(pos(4.5, 1.5), EPSILON));
+        assert!(matches!(
+            enemy.state(),
+            EnemyState::Chasing { lost_for, .. } if lost_for < LOSE_TRACK_TIME
+        ));
+        update(&mut enemy, &mut entity, &world, hidden, 1.5);
+        assert_eq!(enemy.state(), EnemyState::Patrolling);
+        assert!(entity.pos.approx_eq(pos(4.5, 1.5), EPSILON));
+    }
+}
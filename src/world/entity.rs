@@ -1,44 +1,358 @@
-use crate::vec2d::Vec2d;
 use std::fmt::Debug;
 
-macro_rules! entities {
-    ($($name: ident: $entity:ty),*) => {
-        #[derive(Debug)]
-        pub enum Entity {
-            $(
-            $name(Box<$entity>),
-            )*
+use crate::angle::Angle;
+use crate::player::Player;
+use crate::vec2d::Vec2d;
+use crate::world::{EntityId, World};
+
+/// Something in the world which isn't a tile, like a barrel or an enemy.
+/// What an entity is made of are optional components, an entity without any just takes up a position.
+#[derive(Debug, Default)]
+pub struct Entity {
+    pub pos: Vec2d,
+    /// The direction the entity is looking at
+    pub facing: Angle,
+    /// How the entity is rendered, entities without a sprite are invisible
+    pub sprite: Option<Sprite>,
+    /// The collision shape around [Entity::pos], rays pass through entities without a collider
+    pub collider: Option<Shape>,
+    /// Entities without health can't be damaged
+    pub health: Option<Health>,
+    /// What the entity does every update, entities without AI just stand around
+    pub ai: Option<Box<dyn Ai>>,
+}
+
+impl Entity {
+    pub fn new(pos: Vec2d) -> Self {
+        Self {
+            pos,
+            ..Self::default()
         }
-        impl EntityTrait for Entity {
-            fn update(&mut self) {
-                match self {
-                    $(
-                    Entity::$name(entity) => entity.update(),
-                    )*
-                }
-            }
-            fn pos(&self) -> Vec2d {
-                match self {
-                    $(
-                    Entity::$name(entity) => entity.pos(),
-                    )*
+    }
+
+    /// An entity which doesn't do anything but stand in the way, like a barrel or a lamp.
+    /// The kind is used as the sprite name.
+    pub fn prop(kind: impl Into<String>, pos: Vec2d) -> Self {
+        Self {
+            sprite: Some(Sprite::new(kind)),
+            collider: Some(Shape::Circle { radius: 0.3 }),
+            ..Self::new(pos)
+        }
+    }
+}
+
+/// A billboard which always faces the camera
+#[derive(Clone, PartialEq, Debug)]
+pub struct Sprite {
+    /// The name of the sprite texture, see [Textures::sprite](crate::texture::Textures::sprite).
+    /// The frame is picked by the angle the entity is viewed from relative to [Entity::facing].
+    pub name: String,
+    /// The height and width in tiles
+    pub size: f64,
+}
+
+impl Sprite {
+    /// A sprite one tile high
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            size: 1.0,
+        }
+    }
+}
+
+/// A collision shape centered on the position of an entity
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Shape {
+    Circle {
+        radius: f64,
+    },
+    /// An axis aligned box
+    Aabb {
+        half_size: Vec2d,
+    },
+}
+
+impl Shape {
+    /// The radius of the smallest circle around the shape, entities collide with the tiles as this circle,
+    /// see [World::move_circle]
+    pub fn radius(self) -> f64 {
+        match self {
+            Shape::Circle { radius } => radius,
+            Shape::Aabb { half_size } => half_size.magnitude(),
+        }
+    }
+
+    /// Intersects the ray from `pos` in `direction` with the shape around `center`.
+    /// Returns how far along the ray the shape is entered, in multiples of the direction, and the normal of the surface hit.
+    /// Rays starting inside of the shape don't hit it.
+    pub fn cast_ray(self, center: Vec2d, pos: Vec2d, direction: Vec2d) -> Option<(f64, Vec2d)> {
+        let offset = pos - center;
+        match self {
+            Shape::Circle { radius } => {
+                // solve |offset + direction * t| = radius for t
+                let a = direction.dot(direction);
+                let b = 2.0 * offset.dot(direction);
+                let c = offset.dot(offset) - radius.powi(2);
+                let discriminant = b.powi(2) - 4.0 * a * c;
+                if c <= 0.0 || a == 0.0 || discriminant < 0.0 {
+                    return None;
                 }
+                let t = (-b - discriminant.sqrt()) / (2.0 * a);
+                (t >= 0.0).then(|| (t, (offset + direction * t) / radius))
             }
-            fn set_pos(&mut self, pos: Vec2d) {
-                match self {
-                    $(
-                    Entity::$name(entity) => entity.set_pos(pos),
-                    )*
+            Shape::Aabb { half_size } => {
+                // the range of t inside of the box on each axis
+                let slab = |offset: f64, direction: f64, half_size: f64| {
+                    if direction == 0.0 {
+                        (offset.abs() < half_size).then_some((f64::NEG_INFINITY, f64::INFINITY))
+                    } else {
+                        let near = (-half_size.copysign(direction) - offset) / direction;
+                        let far = (half_size.copysign(direction) - offset) / direction;
+                        Some((near, far))
+                    }
+                };
+                let (near_x, far_x) = slab(offset.x, direction.x, half_size.x)?;
+                let (near_y, far_y) = slab(offset.y, direction.y, half_size.y)?;
+                let near = near_x.max(near_y);
+                if near < 0.0 || near > far_x.min(far_y) {
+                    return None;
                 }
+                let normal = if near_x >= near_y {
+                    Vec2d {
+                        x: -direction.x.signum(),
+                        y: 0.0,
+                    }
+                } else {
+                    Vec2d {
+                        x: 0.0,
+                        y: -direction.y.signum(),
+                    }
+                };
+                Some((near, normal))
             }
         }
-    };
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Health {
+    current: f64,
+    max: f64,
+}
+
+impl Health {
+    /// Full health
+    pub fn new(max: f64) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn current(&self) -> f64 {
+        self.current
+    }
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Takes the damage, the health doesn't go below 0
+    pub fn damage(&mut self, amount: f64) {
+        self.current = (self.current - amount).max(0.0);
+    }
+
+    /// Heals the amount, the health doesn't go above the maximum
+    pub fn heal(&mut self, amount: f64) {
+        self.current = (self.current + amount).min(self.max);
+    }
 }
 
-entities!(Other: dyn EntityTrait);
+/// The behavior of an entity
+pub trait Ai: Debug {
+    /// Called every simulation step.
+    /// The entity is taken out of the world while it's updated, so the world in the context only holds the other entities.
+    fn update(&mut self, entity: &mut Entity, context: &mut UpdateContext<'_>);
+}
+
+/// What an [Ai] can see and do during an update
+pub struct UpdateContext<'a> {
+    /// The seconds since the last update
+    pub dt: f64,
+    /// The id of the updated entity
+    pub id: EntityId,
+    /// The tiles and the other entities
+    pub world: &'a World,
+    pub player: &'a Player,
+    /// Changes to the other entities, they are applied once every entity was updated
+    pub commands: &'a mut Commands,
+}
+
+/// Changes to the entities of a world which are applied later, see [World::apply]
+#[derive(Debug, Default)]
+pub struct Commands {
+    commands: Vec<Command>,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Spawn(Entity),
+    Despawn(EntityId),
+    Move(EntityId, Vec2d),
+}
+
+impl Commands {
+    pub fn spawn(&mut self, entity: Entity) {
+        self.commands.push(Command::Spawn(entity));
+    }
+    pub fn despawn(&mut self, id: EntityId) {
+        self.commands.push(Command::Despawn(id));
+    }
+    pub fn move_entity(&mut self, id: EntityId, pos: Vec2d) {
+        self.commands.push(Command::Move(id, pos));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+impl IntoIterator for Commands {
+    type Item = Command;
+    type IntoIter = std::vec::IntoIter<Command>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::player::Player;
+    use crate::vec2d::Vec2d;
+    use crate::world::entity::{Ai, Commands, Entity, Health, Shape, UpdateContext};
+    use crate::world::tile::Tile::{Air, Wall};
+    use crate::world::{EntityId, World};
+
+    /// Walks towards the player until a wall is in the way, on its first update it spawns a lamp and pushes the barrel,
+    /// on the second one it despawns itself
+    #[derive(Debug)]
+    struct TestAi {
+        barrel: EntityId,
+        updates: u32,
+    }
+
+    impl Ai for TestAi {
+        fn update(&mut self, entity: &mut Entity, context: &mut UpdateContext<'_>) {
+            // only the other entities are in the world
+            assert!(context.world.entity(context.id).is_none());
+            assert!(context.world.entity(self.barrel).is_some());
+
+            let next = entity.pos + (context.player.pos - entity.pos).normalize() * context.dt;
+            if !context.world.is_solid_at(next) {
+                entity.pos = next;
+            }
+            self.updates += 1;
+            if self.updates == 1 {
+                context.commands.spawn(Entity::prop("lamp", entity.pos));
+                let barrel = context.world.entity(self.barrel).unwrap().pos;
+                context
+                    .commands
+                    .move_entity(self.barrel, barrel - Vec2d { x: 1.0, y: 0.0 });
+            } else {
+                context.commands.despawn(context.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_entity_update() {
+        let mut world = World::from_rows(&[[Air, Air, Wall, Air, Air]]).unwrap();
+        let barrel = world.add_entity(Entity::prop("barrel", Vec2d { x: 4.5, y: 0.5 }));
+        let walker = world.add_entity(Entity {
+            ai: Some(Box::new(TestAi { barrel, updates: 0 })),
+            ..Entity::new(Vec2d { x: 0.5, y: 0.5 })
+        });
+        let player = Player::new(Vec2d { x: 4.5, y: 0.5 }, Angle::default());
+
+        world.update(1.0, &player);
+        assert_eq!(world.entity(walker).unwrap().pos, Vec2d { x: 1.5, y: 0.5 });
+        assert_eq!(world.entity(barrel).unwrap().pos, Vec2d { x: 3.5, y: 0.5 });
+        assert_eq!(world.entities().count(), 3);
+
+        // the wall is in the way
+        world.update(1.0, &player);
+        assert!(world.entity(walker).is_none());
+        assert_eq!(world.entities().count(), 2);
+    }
+
+    #[test]
+    fn test_stale_id() {
+        let mut world = World::from_rows(&[[Air, Air, Air]]).unwrap();
+        let barrel = world.add_entity(Entity::prop("barrel", Vec2d { x: 0.5, y: 0.5 }));
+        let mut commands = Commands::default();
+        commands.despawn(barrel);
+        commands.spawn(Entity::prop("lamp", Vec2d { x: 1.5, y: 0.5 }));
+        world.apply(commands);
+        // the lamp reuses the slot of the barrel
+        let (lamp, _) = world.entities().next().unwrap();
+        assert_ne!(lamp, barrel);
+        assert!(world.entity(barrel).is_none());
+
+        let mut commands = Commands::default();
+        commands.move_entity(barrel, Vec2d { x: 2.5, y: 0.5 });
+        commands.despawn(barrel);
+        world.apply(commands);
+        assert_eq!(world.entity(lamp).unwrap().pos, Vec2d { x: 1.5, y: 0.5 });
+        assert!(world.remove_entity(barrel).is_none());
+        assert!(world.remove_entity(lamp).is_some());
+        assert_eq!(world.entities().count(), 0);
+    }
+
+    #[test]
+    fn test_health() {
+        let mut health = Health::new(10.0);
+        health.damage(4.0);
+        assert_eq!(health.current(), 6.0);
+        health.heal(10.0);
+        assert_eq!(health.current(), 10.0);
+        health.damage(15.0);
+        assert_eq!(health.current(), 0.0);
+        assert!(health.is_dead());
+    }
+
+    #[test]
+    fn test_shape_cast_ray() {
+        let center = Vec2d { x: 3.0, y: 1.0 };
+        let pos = Vec2d { x: 1.0, y: 1.0 };
+        let east = Vec2d { x: 2.0, y: 0.0 };
+
+        let circle = Shape::Circle { radius: 0.5 };
+        assert_eq!(
+            circle.cast_ray(center, pos, east),
+            Some((0.75, Vec2d { x: -1.0, y: 0.0 }))
+        );
+        assert_eq!(circle.cast_ray(center, pos, -east), None);
+        assert_eq!(circle.cast_ray(center, center, east), None);
+        let above = Vec2d { x: 1.0, y: 1.6 };
+        assert_eq!(circle.cast_ray(center, above, east), None);
 
-pub trait EntityTrait: Debug {
-    fn update(&mut self);
-    fn pos(&self) -> Vec2d;
-    fn set_pos(&mut self, pos: Vec2d);
+        let aabb = Shape::Aabb {
+            half_size: Vec2d { x: 0.5, y: 0.25 },
+        };
+        assert_eq!(
+            aabb.cast_ray(center, pos, east),
+            Some((0.75, Vec2d { x: -1.0, y: 0.0 }))
+        );
+        let below = Vec2d { x: 3.25, y: 0.0 };
+        let north = Vec2d { x: 0.0, y: 1.0 };
+        assert_eq!(
+            aabb.cast_ray(center, below, north),
+            Some((0.75, Vec2d { x: 0.0, y: -1.0 }))
+        );
+        assert_eq!(aabb.cast_ray(center, above, east), None);
+        assert_eq!(aabb.cast_ray(center, center, east), None);
+    }
 }
@@ -0,0 +1,438 @@
+//! A plain text format for worlds.
+//!
+//! A map file starts with a header followed by a line containing only `---` and the tile rows:
+//! ```text
+//! # comments start with a '#'
+//! spawn 1.5 1.5 90
+//! entity barrel 2.5 1.5
+//! entity enemy 1.5 1.5 3.5 1.5 1.5 1.5
+//! ---
+//! #####
+//! #...#
+//! #####
+//! ```
+//! `spawn <x> <y> <direction in degree>` is required once, `entity <kind> <x> <y>` is optional and can be repeated.
+//! The spawn and the entities have to be on tiles which aren't solid.
+//! Any further `<x> <y>` pairs after an entity's position are its waypoints.
+//! Every character of a row is a tile, see [Tile::from_char].
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::angle::Angle;
+use crate::vec2d::Vec2d;
+use crate::world::tile::Tile;
+use crate::world::World;
+
+const SEPARATOR: &str = "---";
+const COMMENT: char = '#';
+
+/// Where the player starts in a world
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Spawn {
+    pub pos: Vec2d,
+    pub direction: Angle,
+}
+
+/// An entity declared in a map.
+/// The kind is only a name, it's up to the game to create the matching entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntityPlacement {
+    pub kind: String,
+    pub pos: Vec2d,
+    /// The points an entity walks along, e.g. the patrol of an enemy
+    pub waypoints: Vec<Vec2d>,
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    /// A header line that couldn't be parsed
+    InvalidHeader {
+        line: usize,
+        message: String,
+    },
+    MissingSeparator,
+    MissingSpawn,
+    MissingTiles,
+    /// An empty line before the last row, only trailing empty lines are allowed
+    EmptyRow {
+        line: usize,
+    },
+    UnknownTile {
+        line: usize,
+        column: usize,
+        char: char,
+    },
+    /// A row with a different length than the first row
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for MapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "failed to access map file: {err}"),
+            MapError::InvalidHeader { line, message } => {
+                write!(f, "line {line}: invalid header: {message}")
+            }
+            MapError::MissingSeparator => {
+                write!(f, "missing '{SEPARATOR}' between the header and the tiles")
+            }
+            MapError::MissingSpawn => write!(f, "missing 'spawn' in the header"),
+            MapError::MissingTiles => write!(f, "the map doesn't contain any tiles"),
+            MapError::EmptyRow { line } => write!(f, "line {line}: empty line before the last row"),
+            MapError::UnknownTile { line, column, char } => {
+                write!(f, "line {line}, column {column}: unknown tile '{char}'")
+            }
+            MapError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: row has {found} tiles but the first row has {expected}"
+            ),
+        }
+    }
+}
+
+impl Error for MapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MapError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MapError {
+    fn from(value: io::Error) -> Self {
+        MapError::Io(value)
+    }
+}
+
+impl World {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapError> {
+        let source = fs::read_to_string(path)?;
+        Self::parse_map(&source)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MapError> {
+        fs::write(path, self.to_map_string())?;
+        Ok(())
+    }
+
+    pub fn parse_map(source: &str) -> Result<Self, MapError> {
+        // line numbers start at 1
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        let mut spawn = None;
+        let mut spawn_line = 0;
+        let mut entity_placements = Vec::new();
+        // the line of every entity placement, for errors about its position
+        let mut placement_lines = Vec::new();
+        let mut found_separator = false;
+        for (line_number, line) in lines.by_ref() {
+            let line = line.trim();
+            if line == SEPARATOR {
+                found_separator = true;
+                break;
+            }
+            if line.is_empty() || line.starts_with(COMMENT) {
+                continue;
+            }
+
+            let invalid = |message: &str| MapError::InvalidHeader {
+                line: line_number,
+                message: message.to_string(),
+            };
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("spawn") => {
+                    if spawn.is_some() {
+                        return Err(invalid(&format!(
+                            "the spawn was already declared in line {spawn_line}"
+                        )));
+                    }
+                    let [x, y, direction] = parse_numbers(&mut words)
+                        .ok_or_else(|| invalid("expected 'spawn <x> <y> <direction>'"))?;
+                    spawn = Some(Spawn {
+                        pos: Vec2d { x, y },
+                        direction: Angle::from_degree(direction),
+                    });
+                    spawn_line = line_number;
+                }
+                Some("entity") => {
+                    let kind = words
+                        .next()
+                        .ok_or_else(|| invalid("expected 'entity <kind> <x> <y>'"))?;
+                    let mut points = parse_points(&mut words)
+                        .filter(|points| !points.is_empty())
+                        .ok_or_else(|| invalid("expected 'entity <kind> <x> <y> [<x> <y>]...'"))?;
+                    let pos = points.remove(0);
+                    entity_placements.push(EntityPlacement {
+                        kind: kind.to_string(),
+                        pos,
+                        waypoints: points,
+                    });
+                    placement_lines.push(line_number);
+                }
+                Some(key) => return Err(invalid(&format!("unknown key '{key}'"))),
+                None => unreachable!("empty lines are skipped"),
+            }
+        }
+        if !found_separator {
+            return Err(MapError::MissingSeparator);
+        }
+
+        let mut width = None;
+        let mut height = 0;
+        let mut tiles = Vec::new();
+        // trailing empty lines aren't rows, but there can't be empty lines in between them
+        let mut empty_line = None;
+        for (line_number, line) in lines {
+            if line.is_empty() {
+                empty_line.get_or_insert(line_number);
+                continue;
+            }
+            if let Some(line) = empty_line {
+                return Err(MapError::EmptyRow { line });
+            }
+
+            let mut row_width = 0;
+            for (column, char) in line.chars().enumerate() {
+                let tile = Tile::from_char(char).ok_or(MapError::UnknownTile {
+                    line: line_number,
+                    column: column + 1,
+                    char,
+                })?;
+                tiles.push(tile);
+                row_width += 1;
+            }
+
+            let expected = *width.get_or_insert(row_width);
+            if row_width != expected {
+                return Err(MapError::RaggedRow {
+                    line: line_number,
+                    expected,
+                    found: row_width,
+                });
+            }
+            height += 1;
+        }
+        let width = width.ok_or(MapError::MissingTiles)?;
+        let spawn = spawn.ok_or(MapError::MissingSpawn)?;
+
+        let mut world =
+            World::from_tiles(width, height, tiles).expect("all rows have the same length");
+        let check_pos = |pos: Vec2d, line: usize, name: &str| {
+            let message = if world.tile_vec(pos).is_none() {
+                format!("the {name} is outside of the tiles")
+            } else if world.is_solid_at(pos) {
+                format!("the {name} is inside of a solid tile")
+            } else {
+                return Ok(());
+            };
+            Err(MapError::InvalidHeader { line, message })
+        };
+        check_pos(spawn.pos, spawn_line, "spawn")?;
+        for (placement, &line) in entity_placements.iter().zip(&placement_lines) {
+            check_pos(placement.pos, line, "entity")?;
+        }
+        world.set_spawn(spawn);
+        world.entity_placements = entity_placements;
+        Ok(world)
+    }
+
+    pub fn to_map_string(&self) -> String {
+        let spawn = self.spawn();
+        let mut out = format!(
+            "spawn {} {} {}\n",
+            spawn.pos.x,
+            spawn.pos.y,
+            spawn.direction.degree()
+        );
+        for placement in self.entity_placements() {
+            out += &format!(
+                "entity {} {} {}",
+                placement.kind, placement.pos.x, placement.pos.y
+            );
+            for waypoint in &placement.waypoints {
+                out += &format!(" {} {}", waypoint.x, waypoint.y);
+            }
+            out.push('\n');
+        }
+        out += SEPARATOR;
+        out.push('\n');
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let tile = self.tile(x, y).expect("x and y are inside the world");
+                out.push(tile.to_char());
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn parse_numbers<'a, const N: usize>(
+    words: &mut impl Iterator<Item = &'a str>,
+) -> Option<[f64; N]> {
+    let mut numbers = [0.0; N];
+    for number in numbers.iter_mut() {
+        *number = parse_number(words.next()?)?;
+    }
+    // there mustn't be anything after the numbers
+    words.next().is_none().then_some(numbers)
+}
+
+/// Parses a finite number, infinity and NaN aren't valid coordinates or directions
+fn parse_number(word: &str) -> Option<f64> {
+    f64::from_str(word).ok().filter(|number| number.is_finite())
+}
+
+/// Parses the rest of the words as pairs of coordinates
+fn parse_points<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<Vec<Vec2d>> {
+    let mut points = Vec::new();
+    while let Some(x) = words.next() {
+        let x = parse_number(x)?;
+        let y = parse_number(words.next()?)?;
+        points.push(Vec2d { x, y });
+    }
+    Some(points)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vec2d::Vec2d;
+    use crate::world::map::MapError;
+    use crate::world::tile::Tile::{Air, Wall};
+    use crate::world::World;
+
+    const MAP: &str = "# test map
+spawn 1.5 1.5 90
+entity barrel 2.5 1.5
+entity enemy 1.5 1.5 2.5 1.5 1.5 1.5
+---
+####
+#..#
+####
+";
+
+    #[test]
+    fn test_roundtrip() {
+        let world = World::parse_map(MAP).unwrap();
+        assert_eq!(world.width(), 4);
+        assert_eq!(world.height(), 3);
+        assert_eq!(world.tile(0, 0), Some(&Wall));
+        assert_eq!(world.tile(1, 1), Some(&Air));
+        assert_eq!(world.spawn().pos, Vec2d { x: 1.5, y: 1.5 });
+        assert_eq!(world.spawn().direction.degree(), 90.0);
+        assert_eq!(world.entity_placements()[0].kind, "barrel");
+        assert!(world.entity_placements()[0].waypoints.is_empty());
+        assert_eq!(
+            world.entity_placements()[1].waypoints,
+            [Vec2d { x: 2.5, y: 1.5 }, Vec2d { x: 1.5, y: 1.5 }]
+        );
+
+        let reparsed = World::parse_map(&world.to_map_string()).unwrap();
+        assert_eq!(reparsed.to_map_string(), world.to_map_string());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            World::parse_map("spawn 1 1 0\n---\n##\n#x\n"),
+            Err(MapError::UnknownTile {
+                line: 4,
+                column: 2,
+                char: 'x'
+            })
+        ));
+        assert!(matches!(
+            World::parse_map("spawn 1 1 0\n---\n##\n#\n"),
+            Err(MapError::RaggedRow {
+                line: 4,
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            World::parse_map("---\n##\n"),
+            Err(MapError::MissingSpawn)
+        ));
+        assert!(matches!(
+            World::parse_map("spawn 1 one 0\n---\n##\n"),
+            Err(MapError::InvalidHeader { line: 1, .. })
+        ));
+        assert!(matches!(
+            World::parse_map("spawn 1 1 0\nentity enemy 1 1 2\n---\n##\n"),
+            Err(MapError::InvalidHeader { line: 2, .. })
+        ));
+        assert!(matches!(
+            World::parse_map("spawn 0.5 0.5 0\n---\n..\n\n..\n"),
+            Err(MapError::EmptyRow { line: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_trailing_empty_lines() {
+        let world = World::parse_map("spawn 0.5 0.5 0\n---\n..\n..\n\n\n").unwrap();
+        assert_eq!(world.height(), 2);
+    }
+
+    #[test]
+    fn test_invalid_spawn() {
+        let parse = |spawn: &str| World::parse_map(&format!("# spawn\n{spawn}\n---\n#.\n"));
+        assert!(parse("spawn 1.5 0.5 0").is_ok());
+        assert!(matches!(
+            parse("spawn 0.5 0.5 0"),
+            Err(MapError::InvalidHeader { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("spawn 1.5 1.5 0"),
+            Err(MapError::InvalidHeader { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("spawn -0.5 0.5 0"),
+            Err(MapError::InvalidHeader { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("spawn 1.5 0.5 inf"),
+            Err(MapError::InvalidHeader { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("spawn 1.5 0.5 0\nspawn 1.5 0.5 90"),
+            Err(MapError::InvalidHeader { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_entity() {
+        let parse = |entity: &str| {
+            World::parse_map(&format!("spawn 1.5 0.5 0\n# entity\n{entity}\n---\n#.\n"))
+        };
+        assert!(parse("entity barrel 1.5 0.5").is_ok());
+        for entity in [
+            "entity barrel NaN 0.5",
+            "entity barrel 1.5 -inf",
+            "entity enemy 1.5 0.5 1.5 inf",
+            "entity barrel 0.5 0.5",
+            "entity barrel 2.5 0.5",
+        ] {
+            assert!(
+                matches!(parse(entity), Err(MapError::InvalidHeader { line: 3, .. })),
+                "{entity}"
+            );
+        }
+    }
+}
@@ -1,85 +1,455 @@
-use conv::{ApproxFrom, ApproxInto};
+use conv::ApproxInto;
+use std::collections::HashMap;
 use std::mem::swap;
-use std::ops::Deref;
 
+use crate::angle::Angle;
+use crate::texture::Textures;
 use crate::vec2d::Vec2d;
 use slab::Slab;
 
-use crate::world::entity::{Entity, EntityTrait};
+use crate::player::Player;
+use crate::world::door::Door;
+use crate::world::enemy::Enemy;
+use crate::world::entity::{Command, Commands, Entity, UpdateContext};
+use crate::world::map::{EntityPlacement, Spawn};
+use crate::world::raycast::GridTraversal;
 use crate::world::tile::Tile;
 
+pub mod collision;
+pub mod door;
+pub mod enemy;
 pub mod entity;
+pub mod map;
+pub mod pathfinding;
 pub mod player;
+pub mod raycast;
 pub mod render;
 pub mod tile;
 
 #[derive(Debug)]
 pub struct World {
-    tiles: [[Tile; World::WIDTH]; World::HEIGHT],
-    entities: Slab<Entity>,
+    width: usize,
+    height: usize,
+    /// The tiles of the world stored row by row, see [World::index]
+    tiles: Vec<Tile>,
+    /// The state of every [Tile::Door] by its coordinates
+    doors: HashMap<[usize; 2], Door>,
+    /// None while the entity is taken out to be updated, see [World::update]
+    entities: Slab<Option<Entity>>,
+    /// The generation of every slot of the entities, it goes up when the entity in the slot is removed
+    generations: Vec<u32>,
+    spawn: Spawn,
+    entity_placements: Vec<EntityPlacement>,
+    textures: Textures,
 }
 
 impl World {
-    pub const WIDTH: usize = 10;
-    pub const HEIGHT: usize = 10;
-
-    pub fn from_tiles(tiles: [[Tile; World::WIDTH]; World::HEIGHT]) -> Self {
+    /// Creates a world of the given size filled with [Tile::default].
+    /// Panics when the amount of tiles overflows.
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width
+            .checked_mul(height)
+            .expect("the amount of tiles overflows");
         Self {
+            width,
+            height,
+            tiles: vec![Tile::default(); size],
+            doors: HashMap::new(),
+            entities: Default::default(),
+            generations: Vec::new(),
+            spawn: Spawn::default(),
+            entity_placements: Vec::new(),
+            textures: Textures::default(),
+        }
+    }
+
+    /// Creates a world from tiles stored row by row.
+    /// Returns None when the amount of tiles doesn't match the size or the size overflows.
+    pub fn from_tiles(width: usize, height: usize, tiles: Vec<Tile>) -> Option<Self> {
+        if width.checked_mul(height) != Some(tiles.len()) {
+            return None;
+        }
+        let doors = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .filter(|[x, y]| tiles[y * width + x] == Tile::Door)
+            .map(|coords| (coords, Door::default()))
+            .collect();
+        Some(Self {
+            width,
+            height,
             tiles,
+            doors,
             entities: Default::default(),
+            generations: Vec::new(),
+            spawn: Spawn::default(),
+            entity_placements: Vec::new(),
+            textures: Textures::default(),
+        })
+    }
+
+    /// Creates a world from a list of rows where `rows[y][x]` is the tile at x, y.
+    /// Returns None when the rows don't have the same length.
+    pub fn from_rows<R>(rows: &[R]) -> Option<Self>
+    where
+        R: AsRef<[Tile]>,
+    {
+        let height = rows.len();
+        let width = rows.first().map(|row| row.as_ref().len()).unwrap_or(0);
+
+        let mut tiles = Vec::with_capacity(width.checked_mul(height)?);
+        for row in rows {
+            let row = row.as_ref();
+            if row.len() != width {
+                return None;
+            }
+            tiles.extend_from_slice(row);
         }
+        Self::from_tiles(width, height, tiles)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// The coordinates of a cell when it's inside the world
+    fn cell_coords(&self, [x, y]: [i64; 2]) -> Option<[usize; 2]> {
+        let x = usize::try_from(x).ok()?;
+        let y = usize::try_from(y).ok()?;
+        (x < self.width() && y < self.height()).then_some([x, y])
     }
 
     pub fn tile_vec(&self, vec: Vec2d) -> Option<&Tile> {
-        let coords = <Vec2d as ApproxInto<[usize; 2]>>::approx_into(vec).ok()?;
+        // floor first so that e.g. -0.5 doesn't get truncated into the first tile
+        let coords = <Vec2d as ApproxInto<[usize; 2]>>::approx_into(vec.floor()).ok()?;
         self.tile(coords[0], coords[1])
     }
     pub fn tile(&self, x: usize, y: usize) -> Option<&Tile> {
-        self.tiles.get(x).and_then(|tiles| tiles.get(y))
+        self.index(x, y).map(|index| &self.tiles[index])
     }
 
     pub fn set_tile(&mut self, x: usize, y: usize, mut new_tile: Tile) -> Option<Tile> {
-        let tile_at = self.tiles.get_mut(x).and_then(|tiles| tiles.get_mut(y))?;
-        swap(tile_at, &mut new_tile);
+        let index = self.index(x, y)?;
+        swap(&mut self.tiles[index], &mut new_tile);
+        if self.tiles[index] == Tile::Door {
+            self.doors.entry([x, y]).or_default();
+        } else {
+            self.doors.remove(&[x, y]);
+        }
         Some(new_tile)
     }
 
+    /// Whether something at `pos` collides with the world.
+    /// Doors only block the part which isn't open yet, everything outside of the world is solid.
+    pub fn is_solid_at(&self, pos: Vec2d) -> bool {
+        match self.tile_vec(pos) {
+            None => true,
+            Some(Tile::Door) => {
+                let [x, y] = [pos.x.floor() as usize, pos.y.floor() as usize];
+                let along = if self.door_faces_x(x, y) {
+                    pos.y - y as f64
+                } else {
+                    pos.x - x as f64
+                };
+                self.door(x, y).is_none_or(|door| door.blocks(along))
+            }
+            Some(tile) => tile.is_solid(),
+        }
+    }
+
+    pub fn door(&self, x: usize, y: usize) -> Option<&Door> {
+        self.doors.get(&[x, y])
+    }
+    pub fn door_mut(&mut self, x: usize, y: usize) -> Option<&mut Door> {
+        self.doors.get_mut(&[x, y])
+    }
+
+    /// Whether the door at x, y faces the x axis, so it's passed by walking along the x axis.
+    /// This is the case when the tiles above and below it are solid, otherwise it faces the y axis.
+    pub fn door_faces_x(&self, x: usize, y: usize) -> bool {
+        let solid = |y: Option<usize>| {
+            y.and_then(|y| self.tile(x, y))
+                .is_none_or(|tile| tile.is_solid())
+        };
+        solid(y.checked_sub(1)) && solid(y.checked_add(1))
+    }
+
+    /// Finds the door whose tile is entered first when walking from `pos` in `direction` for at most `max_distance`,
+    /// see [GridTraversal]. Other solid tiles stop the search, open doors are found as well.
+    pub fn door_in_front(
+        &self,
+        pos: Vec2d,
+        direction: Angle,
+        max_distance: f64,
+    ) -> Option<[usize; 2]> {
+        // the direction is normalized, so the ray parameter is the distance
+        for entry in GridTraversal::new(pos, direction.vec())? {
+            if entry.t > max_distance {
+                return None;
+            }
+            let [x, y] = self.cell_coords(entry.cell)?;
+            match self.tile(x, y)? {
+                Tile::Door => return Some([x, y]),
+                tile if tile.is_solid() => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    pub fn spawn(&self) -> Spawn {
+        self.spawn
+    }
+    pub fn set_spawn(&mut self, spawn: Spawn) {
+        self.spawn = spawn;
+    }
+
+    /// The entities declared in the map this world was loaded from
+    pub fn entity_placements(&self) -> &[EntityPlacement] {
+        &self.entity_placements
+    }
+
+    /// Adds an entity for every entity placement of the map.
+    /// Enemies patrol the waypoints of their placement, see [Enemy::entity], every other kind is a [Entity::prop].
+    pub fn spawn_entities(&mut self) {
+        for placement in self.entity_placements.clone() {
+            let entity = match placement.kind.as_str() {
+                Enemy::KIND => Enemy::entity(placement.pos, placement.waypoints),
+                _ => Entity::prop(placement.kind, placement.pos),
+            };
+            self.add_entity(entity);
+        }
+    }
+
+    pub fn textures(&self) -> &Textures {
+        &self.textures
+    }
+    pub fn set_textures(&mut self, textures: Textures) {
+        self.textures = textures;
+    }
+
     pub fn entity(&self, id: EntityId) -> Option<&Entity> {
-        self.entities.get(*id)
+        if !self.is_current(id) {
+            return None;
+        }
+        self.entities.get(id.index)?.as_ref()
     }
     pub fn entity_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
-        self.entities.get_mut(*id)
+        if !self.is_current(id) {
+            return None;
+        }
+        self.entities.get_mut(id.index)?.as_mut()
+    }
+    pub fn entities(&self) -> impl Iterator<Item = (EntityId, &Entity)> {
+        self.entities
+            .iter()
+            .filter_map(|(index, entity)| Some((self.entity_id(index), entity.as_ref()?)))
     }
     pub fn add_entity(&mut self, entity: Entity) -> EntityId {
-        EntityId(self.entities.insert(entity))
+        let index = self.entities.insert(Some(entity));
+        if index == self.generations.len() {
+            self.generations.push(0);
+        }
+        self.entity_id(index)
     }
-    pub fn remove_entity(&mut self, id: EntityId) -> Entity {
-        self.entities.remove(*id)
+    pub fn remove_entity(&mut self, id: EntityId) -> Option<Entity> {
+        if !self.is_current(id) {
+            return None;
+        }
+        let entity = self.entities.try_remove(id.index).flatten();
+        // ids of the removed entity don't refer to the next entity in the slot
+        self.generations[id.index] = self.generations[id.index].wrapping_add(1);
+        entity
     }
 
-    pub fn update(&mut self) {
-        for (_, entity) in self.entities.iter_mut() {
-            entity.update();
+    /// The id of the entity currently in the slot
+    fn entity_id(&self, index: usize) -> EntityId {
+        EntityId {
+            index,
+            generation: self.generations[index],
         }
     }
-}
 
-#[derive(Copy, Clone, PartialEq)]
-pub struct EntityId(usize);
+    /// Whether the id refers to the entity currently in its slot, and not to one which was removed
+    fn is_current(&self, id: EntityId) -> bool {
+        self.entities.contains(id.index) && self.generations[id.index] == id.generation
+    }
 
-impl Deref for EntityId {
-    type Target = usize;
+    /// Damages an entity with [Health](crate::world::entity::Health), it's despawned when it dies.
+    /// Returns whether the entity died, entities without health aren't damaged.
+    pub fn damage_entity(&mut self, id: EntityId, amount: f64) -> bool {
+        let Some(health) = self
+            .entity_mut(id)
+            .and_then(|entity| entity.health.as_mut())
+        else {
+            return false;
+        };
+        health.damage(amount);
+        if !health.is_dead() {
+            return false;
+        }
+        self.remove_entity(id);
+        true
+    }
+
+    /// Advances the world by `dt` seconds.
+    /// Every entity with an [Ai](crate::world::entity::Ai) is updated, then the commands they gave are applied.
+    pub fn update(&mut self, dt: f64, player: &Player) {
+        for door in self.doors.values_mut() {
+            door.update(dt);
+        }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+        let mut commands = Commands::default();
+        let indices: Vec<usize> = self.entities.iter().map(|(index, _)| index).collect();
+        for index in indices {
+            // taken out of the world while it's updated, so the world can be borrowed by the context
+            let Some(mut entity) = self.entities[index].take() else {
+                continue;
+            };
+            if let Some(mut ai) = entity.ai.take() {
+                let mut context = UpdateContext {
+                    dt,
+                    id: self.entity_id(index),
+                    world: self,
+                    player,
+                    commands: &mut commands,
+                };
+                ai.update(&mut entity, &mut context);
+                // unless the AI replaced itself
+                if entity.ai.is_none() {
+                    entity.ai = Some(ai);
+                }
+            }
+            self.entities[index] = Some(entity);
+        }
+        self.apply(commands);
     }
+
+    /// Applies the commands in the order they were given.
+    /// Commands for entities which don't exist anymore are ignored.
+    pub fn apply(&mut self, commands: Commands) {
+        for command in commands {
+            match command {
+                Command::Spawn(entity) => {
+                    self.add_entity(entity);
+                }
+                Command::Despawn(id) => {
+                    self.remove_entity(id);
+                }
+                Command::Move(id, pos) => {
+                    if let Some(entity) = self.entity_mut(id) {
+                        entity.pos = pos;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Refers to an entity of a [World].
+/// The slots of removed entities are reused, the generation tells the entities in the same slot apart,
+/// so an id of a removed entity never refers to another entity.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct EntityId {
+    index: usize,
+    generation: u32,
 }
 
 impl Default for World {
     fn default() -> Self {
-        Self {
-            tiles: [[Tile::default(); Self::WIDTH]; Self::HEIGHT],
-            entities: Slab::default(),
-        }
+        Self::new(10, 10)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::angle::Angle;
+    use crate::consts::world::DOOR_SPEED;
+    use crate::player::Player;
+    use crate::vec2d::Vec2d;
+    use crate::world::door::DoorState;
+    use crate::world::tile::Tile::{Air, Door, Wall};
+    use crate::world::World;
+
+    #[test]
+    fn test_from_rows() {
+        let world = World::from_rows(&[[Wall, Air, Air], [Air, Air, Wall]]).unwrap();
+        assert_eq!(world.width(), 3);
+        assert_eq!(world.height(), 2);
+        assert_eq!(world.tile(0, 0), Some(&Wall));
+        assert_eq!(world.tile(2, 1), Some(&Wall));
+        assert_eq!(world.tile(3, 0), None);
+        assert_eq!(world.tile(0, 2), None);
+
+        assert!(World::from_rows(&[vec![Air, Air], vec![Air]]).is_none());
+        assert!(World::from_tiles(2, 2, vec![Air; 3]).is_none());
+        assert!(World::from_tiles(usize::MAX, 2, Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_tile_vec_bounds() {
+        let mut world = World::new(64, 80);
+        assert_eq!(world.set_tile(63, 79, Wall), Some(Air));
+        assert_eq!(world.set_tile(64, 0, Wall), None);
+
+        assert_eq!(world.tile_vec(Vec2d { x: 63.5, y: 79.9 }), Some(&Wall));
+        assert_eq!(world.tile_vec(Vec2d { x: -0.5, y: 1.0 }), None);
+        assert_eq!(world.tile_vec(Vec2d { x: 1.0, y: 80.0 }), None);
+    }
+
+    #[test]
+    fn test_door_collision() {
+        let mut world = World::from_rows(&[[Wall, Door, Wall], [Air, Air, Air]]).unwrap();
+        assert!(!world.door_faces_x(1, 0));
+        // the tile is solid, only the open part of the door can be passed
+        assert!(Door.is_solid());
+        let left = Vec2d { x: 1.1, y: 0.5 };
+        let right = Vec2d { x: 1.9, y: 0.5 };
+        assert!(world.is_solid_at(left) && world.is_solid_at(right));
+
+        let door = world
+            .door_in_front(Vec2d { x: 1.5, y: 1.5 }, Angle::from_degree(270.0), 1.0)
+            .unwrap();
+        assert_eq!(door, [1, 0]);
+        world.door_mut(1, 0).unwrap().toggle();
+        assert_eq!(world.door(1, 0).unwrap().state(), DoorState::Opening);
+        world.update(0.5 / DOOR_SPEED, &Player::default());
+        // the door slides towards negative x, so that side is passable first
+        assert!(!world.is_solid_at(left) && world.is_solid_at(right));
+
+        world.update(1.0 / DOOR_SPEED, &Player::default());
+        assert_eq!(world.door(1, 0).unwrap().state(), DoorState::Open);
+        assert!(!world.is_solid_at(right));
+
+        assert_eq!(world.set_tile(1, 0, Air), Some(Door));
+        assert!(world.door(1, 0).is_none());
+    }
+
+    #[test]
+    fn test_door_in_front() {
+        let world =
+            World::from_rows(&[[Air, Door, Air, Wall, Door], [Air, Air, Air, Air, Air]]).unwrap();
+        let pos = Vec2d { x: 0.5, y: 0.5 };
+        let east = Angle::from_degree(0.0);
+        assert_eq!(world.door_in_front(pos, east, 0.5), Some([1, 0]));
+        assert_eq!(world.door_in_front(pos, east, 0.4), None);
+        // the wall hides the door behind it
+        let pos = Vec2d { x: 2.5, y: 0.5 };
+        assert_eq!(world.door_in_front(pos, east, 5.0), None);
+
+        // only the corner of the door's tile is passed
+        let clipping = Angle::from_vec(Vec2d { x: 1.0, y: 0.99 });
+        let pos = Vec2d { x: 0.5, y: 0.5 };
+        assert_eq!(world.door_in_front(pos, clipping, 1.0), Some([1, 0]));
     }
 }
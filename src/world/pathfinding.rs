@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::SQRT_2;
+
+use crate::vec2d::Vec2d;
+use crate::world::World;
+
+/// A tile waiting to be visited, ordered so the [BinaryHeap] pops the lowest estimated cost first
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Node {
+    /// The cost to get here plus the estimated cost to the goal
+    estimate: f64,
+    index: usize,
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The cost of the shortest path between two tiles when there is nothing in the way
+fn octile_distance([from_x, from_y]: [usize; 2], [to_x, to_y]: [usize; 2]) -> f64 {
+    let dx = from_x.abs_diff(to_x) as f64;
+    let dy = from_y.abs_diff(to_y) as f64;
+    dx + dy + (SQRT_2 - 2.0) * dx.min(dy)
+}
+
+fn center([x, y]: [usize; 2]) -> Vec2d {
+    Vec2d {
+        x: x as f64 + 0.5,
+        y: y as f64 + 0.5,
+    }
+}
+
+impl World {
+    /// Finds the shortest path from `from` to `to` over the tiles with A*.
+    ///
+    /// Moves go to the eight neighbours of a tile. Diagonal moves are only taken when both tiles next to them
+    /// can be walked through, so paths don't cut corners.
+    /// The path is the middle of every tile on the way without the start tile and ends at `to`.
+    /// Returns None when `to` can't be reached.
+    pub fn find_path(&self, from: Vec2d, to: Vec2d) -> Option<Vec<Vec2d>> {
+        let start = self.cell_of(from)?;
+        let goal = self.cell_of(to)?;
+        if self.is_solid_at(to) {
+            return None;
+        }
+        if start == goal {
+            return Some(vec![to]);
+        }
+        let walkable = |cell: [usize; 2]| cell == goal || self.is_walkable(cell);
+
+        let width = self.width();
+        let index = |[x, y]: [usize; 2]| y * width + x;
+        let cell = |index: usize| [index % width, index / width];
+        let mut costs = vec![f64::INFINITY; self.width() * self.height()];
+        let mut came_from = vec![None; costs.len()];
+        let mut open = BinaryHeap::new();
+        costs[index(start)] = 0.0;
+        open.push(Node {
+            estimate: octile_distance(start, goal),
+            index: index(start),
+        });
+
+        while let Some(Node {
+            estimate,
+            index: current,
+        }) = open.pop()
+        {
+            let current_cell = cell(current);
+            if current_cell == goal {
+                break;
+            }
+            // a cheaper way to this tile was found after it was queued
+            if estimate > costs[current] + octile_distance(current_cell, goal) {
+                continue;
+            }
+
+            let [x, y] = current_cell;
+            for (dx, dy) in [
+                (-1, 0),
+                (1, 0),
+                (0, -1),
+                (0, 1),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1),
+            ] {
+                let Some(neighbour) = self.cell_coords([x as i64 + dx, y as i64 + dy]) else {
+                    continue;
+                };
+                if !walkable(neighbour) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                if diagonal
+                    && !(self.is_walkable([neighbour[0], y]) && self.is_walkable([x, neighbour[1]]))
+                {
+                    continue;
+                }
+
+                let cost = costs[current] + if diagonal { SQRT_2 } else { 1.0 };
+                let neighbour_index = index(neighbour);
+                if cost < costs[neighbour_index] {
+                    costs[neighbour_index] = cost;
+                    came_from[neighbour_index] = Some(current);
+                    open.push(Node {
+                        estimate: cost + octile_distance(neighbour, goal),
+                        index: neighbour_index,
+                    });
+                }
+            }
+        }
+
+        let mut path = vec![to];
+        let mut current = came_from[index(goal)]?;
+        while current != index(start) {
+            path.push(center(cell(current)));
+            current =
+                came_from[current].expect("every visited tile but the start has a previous one");
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Whether something can walk through the middle of the tile
+    fn is_walkable(&self, cell: [usize; 2]) -> bool {
+        !self.is_solid_at(center(cell))
+    }
+
+    /// The tile containing `pos` when it's inside the world
+    fn cell_of(&self, pos: Vec2d) -> Option<[usize; 2]> {
+        let pos = pos.floor();
+        self.cell_coords([pos.x as i64, pos.y as i64])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{parse_rows, pos};
+
+    #[test]
+    fn test_straight_path() {
+        let world = parse_rows(&["#####", "#...#", "#####"]);
+        let path = world.find_path(pos(1.5, 1.5), pos(3.2, 1.4)).unwrap();
+        assert_eq!(path, [pos(2.5, 1.5), pos(3.2, 1.4)]);
+        assert_eq!(
+            world.find_path(pos(1.5, 1.5), pos(1.2, 1.2)).unwrap(),
+            [pos(1.2, 1.2)]
+        );
+    }
+
+    #[test]
+    fn test_path_around_walls() {
+        let world = parse_rows(&["#####", "#...#", "#.#.#", "#.#.#", "#####"]);
+        let path = world.find_path(pos(1.5, 3.5), pos(3.5, 3.5)).unwrap();
+        // diagonals would cut the corners of the wall in the middle
+        assert_eq!(
+            path,
+            [
+                pos(1.5, 2.5),
+                pos(1.5, 1.5),
+                pos(2.5, 1.5),
+                pos(3.5, 1.5),
+                pos(3.5, 2.5),
+                pos(3.5, 3.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagonal_path() {
+        let world = parse_rows(&["######", "#....#", "#....#", "#....#", "######"]);
+        let path = world.find_path(pos(1.5, 1.5), pos(4.5, 3.5)).unwrap();
+        assert_eq!(path.len(), 3);
+        assert_eq!(path.last(), Some(&pos(4.5, 3.5)));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let world = parse_rows(&["#####", "#.#.#", "#####"]);
+        assert_eq!(world.find_path(pos(1.5, 1.5), pos(3.5, 1.5)), None);
+        assert_eq!(world.find_path(pos(1.5, 1.5), pos(2.5, 1.5)), None);
+        assert_eq!(world.find_path(pos(1.5, 1.5), pos(-1.0, 1.5)), None);
+
+        // the diagonal gap is too narrow
+        let world = parse_rows(&["####", "#.##", "##.#", "####"]);
+        assert_eq!(world.find_path(pos(1.5, 1.5), pos(2.5, 2.5)), None);
+    }
+}
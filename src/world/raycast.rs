@@ -0,0 +1,832 @@
+use crate::direction::Direction;
+use crate::ray::{HitInfo, Ray, Raycastable};
+use crate::vec2d::Vec2d;
+use crate::world::entity::Entity;
+use crate::world::tile::Tile;
+use crate::world::{EntityId, World};
+
+/// A cell of the grid entered by a ray, see [GridTraversal]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CellEntry {
+    pub cell: [i64; 2],
+    /// How far along the ray the cell is entered, in multiples of the ray direction
+    pub t: f64,
+    /// The direction the ray moved in to enter the cell, None for the cell the ray starts in
+    pub direction: Option<Direction>,
+}
+
+/// Walks through the cells of the grid a ray passes, in order, using a DDA.
+///
+/// Every step moves into the neighbour on the axis whose next cell boundary is closer,
+/// so no cell is skipped, including for axis aligned rays and rays going exactly through corners,
+/// which step along the x axis first.
+/// The ray parameter of every boundary is calculated from the start of the ray instead of being summed up,
+/// so it's exact no matter how long the ray gets.
+/// The traversal never ends on its own.
+#[derive(Clone, Debug)]
+pub struct GridTraversal {
+    pos: [f64; 2],
+    direction: [f64; 2],
+    cell: [i64; 2],
+    step: [i64; 2],
+    started: bool,
+}
+
+impl GridTraversal {
+    /// The direction doesn't need to be normalized, [CellEntry::t] is in multiples of it.
+    /// Returns None when the direction is zero or not finite.
+    pub fn new(pos: Vec2d, direction: Vec2d) -> Option<Self> {
+        let finite = direction.x.is_finite() && direction.y.is_finite();
+        if !finite || (direction.x == 0.0 && direction.y == 0.0) {
+            return None;
+        }
+        let step = |direction: f64| {
+            if direction > 0.0 {
+                1
+            } else if direction < 0.0 {
+                -1
+            } else {
+                // never crosses a boundary on this axis
+                0
+            }
+        };
+        Some(Self {
+            pos: [pos.x, pos.y],
+            direction: [direction.x, direction.y],
+            cell: [pos.x.floor() as i64, pos.y.floor() as i64],
+            step: [step(direction.x), step(direction.y)],
+            started: false,
+        })
+    }
+
+    /// The ray parameter of the next cell boundary on the axis
+    fn next_t(&self, axis: usize) -> f64 {
+        let boundary = match self.step[axis] {
+            0 => return f64::INFINITY,
+            1 => self.cell[axis] + 1,
+            _ => self.cell[axis],
+        };
+        (boundary as f64 - self.pos[axis]) / self.direction[axis]
+    }
+}
+
+impl Iterator for GridTraversal {
+    type Item = CellEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(CellEntry {
+                cell: self.cell,
+                t: 0.0,
+                direction: None,
+            });
+        }
+
+        let (next_t_x, next_t_y) = (self.next_t(0), self.next_t(1));
+        let (axis, t) = if next_t_x <= next_t_y {
+            (0, next_t_x)
+        } else {
+            (1, next_t_y)
+        };
+        self.cell[axis] += self.step[axis];
+        let direction = match (axis, self.step[axis] > 0) {
+            (0, true) => Direction::East,
+            (0, false) => Direction::West,
+            (_, true) => Direction::North,
+            (_, false) => Direction::South,
+        };
+        Some(CellEntry {
+            cell: self.cell,
+            t,
+            direction: Some(direction),
+        })
+    }
+}
+
+/// Describes a ray cast into a [World], see [World::cast].
+///
+/// By default the ray is blocked by solid tiles and the closed part of doors, goes on until it leaves the world
+/// and is blocked by the tile it starts in as well.
+#[derive(Copy, Clone, Debug)]
+pub struct RayQuery<F = fn(&Tile) -> bool> {
+    pos: Vec2d,
+    direction: Vec2d,
+    max_distance: f64,
+    ignore_start: bool,
+    ignored_entity: Option<EntityId>,
+    blocks: F,
+}
+
+impl RayQuery {
+    /// The direction doesn't need to be normalized, [HitInfo::perp_distance] is in multiples of it.
+    /// For the ray through a column of a [Camera](crate::camera::Camera) this is the distance to the camera plane.
+    pub fn new(pos: Vec2d, direction: Vec2d) -> Self {
+        Self {
+            pos,
+            direction,
+            max_distance: f64::INFINITY,
+            ignore_start: false,
+            ignored_entity: None,
+            blocks: Tile::is_solid,
+        }
+    }
+}
+
+impl From<Ray> for RayQuery {
+    fn from(ray: Ray) -> Self {
+        Self::new(ray.pos, ray.angle.vec())
+    }
+}
+
+impl<F> RayQuery<F>
+where
+    F: Fn(&Tile) -> bool,
+{
+    /// Nothing further away than `max_distance` is hit, measured in tiles like [HitInfo::distance]
+    pub fn max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Whether the tile the ray starts in is passed through, for rays cast from inside of a wall
+    pub fn ignore_start(mut self, ignore_start: bool) -> Self {
+        self.ignore_start = ignore_start;
+        self
+    }
+
+    /// An entity the ray passes through when casting with [World::cast_objects], like the one shooting the ray
+    pub fn ignore_entity(mut self, id: EntityId) -> Self {
+        self.ignored_entity = Some(id);
+        self
+    }
+
+    /// Decides which tiles block the ray.
+    /// Doors only block with the part of their slab which isn't open.
+    pub fn blocking<G>(self, blocks: G) -> RayQuery<G>
+    where
+        G: Fn(&Tile) -> bool,
+    {
+        RayQuery {
+            pos: self.pos,
+            direction: self.direction,
+            max_distance: self.max_distance,
+            ignore_start: self.ignore_start,
+            ignored_entity: self.ignored_entity,
+            blocks,
+        }
+    }
+}
+
+/// A ray hitting an entity, see [World::cast_objects]
+#[derive(Debug)]
+pub struct EntityHit<'a> {
+    pub id: EntityId,
+    pub entity: &'a Entity,
+    /// Like [HitInfo::distance]
+    pub distance: f64,
+    /// Like [HitInfo::perp_distance]
+    pub perp_distance: f64,
+    pub hit: Vec2d,
+    /// The normal of the shape where it was hit
+    pub normal: Vec2d,
+}
+
+/// The nearest thing a ray hit, either a tile or an entity
+#[derive(Debug)]
+pub enum WorldHit<'a> {
+    Tile(HitInfo<'a, Tile>),
+    Entity(EntityHit<'a>),
+}
+
+impl WorldHit<'_> {
+    pub fn distance(&self) -> f64 {
+        match self {
+            WorldHit::Tile(hit) => hit.distance,
+            WorldHit::Entity(hit) => hit.distance,
+        }
+    }
+
+    pub fn hit(&self) -> Vec2d {
+        match self {
+            WorldHit::Tile(hit) => hit.hit,
+            WorldHit::Entity(hit) => hit.hit,
+        }
+    }
+
+    pub fn normal(&self) -> Vec2d {
+        match self {
+            WorldHit::Tile(hit) => hit.normal(),
+            WorldHit::Entity(hit) => hit.normal,
+        }
+    }
+
+    /// The id of the entity which was hit
+    pub fn entity_id(&self) -> Option<EntityId> {
+        match self {
+            WorldHit::Tile(_) => None,
+            WorldHit::Entity(hit) => Some(hit.id),
+        }
+    }
+}
+
+impl Raycastable for World {
+    type HitObject = Tile;
+
+    fn cast_ray(&self, ray: Ray) -> Option<HitInfo<'_, Self::HitObject>> {
+        self.cast(&RayQuery::from(ray))
+    }
+}
+
+/// The hits along the ray of a [RayQuery] ordered by distance, see [World::cast_all]
+pub struct RayHits<'a, 'q, F> {
+    world: &'a World,
+    query: &'q RayQuery<F>,
+    /// None once the ray left the world or reached the maximum distance
+    traversal: Option<GridTraversal>,
+}
+
+impl<'a, F> Iterator for RayHits<'a, '_, F>
+where
+    F: Fn(&Tile) -> bool,
+{
+    type Item = HitInfo<'a, Tile>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let RayQuery { pos, direction, .. } = *self.query;
+        let length = direction.magnitude();
+        let traversal = self.traversal.as_mut()?;
+        loop {
+            let entry = traversal.next()?;
+            if entry.t * length > self.query.max_distance {
+                break;
+            }
+            // rays never come back once they left the world
+            let Some([x, y]) = self.world.cell_coords(entry.cell) else {
+                break;
+            };
+            let tile = self.world.tile(x, y)?;
+            if !(self.query.blocks)(tile) || (entry.direction.is_none() && self.query.ignore_start)
+            {
+                continue;
+            }
+            if *tile == Tile::Door {
+                match self.world.cast_door(pos, direction, [x, y]) {
+                    Some(hit) if hit.distance <= self.query.max_distance => return Some(hit),
+                    Some(_) => break,
+                    None => continue,
+                }
+            }
+
+            let Some(hit_direction) = entry.direction else {
+                // inside of the tile, the hit counts as one on the face the ray is moving towards the most
+                let hit_direction = if direction.x.abs() >= direction.y.abs() {
+                    if direction.x > 0.0 {
+                        Direction::East
+                    } else {
+                        Direction::West
+                    }
+                } else if direction.y > 0.0 {
+                    Direction::North
+                } else {
+                    Direction::South
+                };
+                return Some(HitInfo {
+                    distance: 0.0,
+                    perp_distance: 0.0,
+                    hit: pos,
+                    hit_object: Some(tile),
+                    cell: [x, y],
+                    hit_direction,
+                    wall_offset: wall_offset(pos, hit_direction),
+                });
+            };
+
+            // put the hit exactly onto the boundary so the offset along the face is exact
+            let mut hit = pos + direction * entry.t;
+            match hit_direction {
+                Direction::East => hit.x = x as f64,
+                Direction::West => hit.x = x as f64 + 1.0,
+                Direction::North => hit.y = y as f64,
+                Direction::South => hit.y = y as f64 + 1.0,
+            }
+            return Some(HitInfo {
+                distance: entry.t * length,
+                perp_distance: entry.t,
+                hit,
+                hit_object: Some(tile),
+                cell: [x, y],
+                hit_direction,
+                wall_offset: wall_offset(hit, hit_direction),
+            });
+        }
+        self.traversal = None;
+        None
+    }
+}
+
+impl World {
+    /// Finds the first tile blocking the ray of the query.
+    ///
+    /// When the ray starts inside of a blocking tile it's hit right away with a distance of 0,
+    /// unless the query ignores the start.
+    pub fn cast<F>(&self, query: &RayQuery<F>) -> Option<HitInfo<'_, Tile>>
+    where
+        F: Fn(&Tile) -> bool,
+    {
+        self.cast_all(query).next()
+    }
+
+    /// Finds every tile blocking the ray of the query, ordered by distance.
+    ///
+    /// The ray passes through the tiles it hits, so this goes on until the ray leaves the world
+    /// or reaches the maximum distance. Use [Tile::is_transparent] to stop at the first tile that can't be seen through.
+    pub fn cast_all<'q, F>(&self, query: &'q RayQuery<F>) -> RayHits<'_, 'q, F>
+    where
+        F: Fn(&Tile) -> bool,
+    {
+        RayHits {
+            world: self,
+            query,
+            traversal: GridTraversal::new(query.pos, query.direction),
+        }
+    }
+
+    /// Finds the first tile or entity with a [Shape](crate::world::entity::Shape) blocking the ray of the query.
+    /// Entities the ray starts inside of are passed through.
+    pub fn cast_objects<F>(&self, query: &RayQuery<F>) -> Option<WorldHit<'_>>
+    where
+        F: Fn(&Tile) -> bool,
+    {
+        let tile_hit = self.cast(query);
+        let max_t = tile_hit
+            .as_ref()
+            .map_or(f64::INFINITY, |hit| hit.perp_distance);
+        let length = query.direction.magnitude();
+
+        let mut entity_hit: Option<EntityHit> = None;
+        for (id, entity) in self.entities() {
+            if query.ignored_entity == Some(id) {
+                continue;
+            }
+            let Some(shape) = entity.collider else {
+                continue;
+            };
+            let Some((t, normal)) = shape.cast_ray(entity.pos, query.pos, query.direction) else {
+                continue;
+            };
+            let closest_t = entity_hit.as_ref().map_or(max_t, |hit| hit.perp_distance);
+            if t < closest_t && t * length <= query.max_distance {
+                entity_hit = Some(EntityHit {
+                    id,
+                    entity,
+                    distance: t * length,
+                    perp_distance: t,
+                    hit: query.pos + query.direction * t,
+                    normal,
+                });
+            }
+        }
+
+        match entity_hit {
+            Some(hit) => Some(WorldHit::Entity(hit)),
+            None => tile_hit.map(WorldHit::Tile),
+        }
+    }
+
+    /// Intersects the ray with the slab of the door in the tile at `cell`.
+    /// The slab is in the middle of the tile and only the part which isn't open can be hit.
+    fn cast_door(
+        &self,
+        pos: Vec2d,
+        direction: Vec2d,
+        [x, y]: [usize; 2],
+    ) -> Option<HitInfo<'_, Tile>> {
+        let door = self.door(x, y)?;
+        let cell = Vec2d {
+            x: x as f64,
+            y: y as f64,
+        };
+
+        let faces_x = self.door_faces_x(x, y);
+        let (ray_pos, ray_direction, slab) = if faces_x {
+            (pos.x, direction.x, cell.x + 0.5)
+        } else {
+            (pos.y, direction.y, cell.y + 0.5)
+        };
+        if ray_direction == 0.0 {
+            return None;
+        }
+        let t = (slab - ray_pos) / ray_direction;
+        if t <= 0.0 {
+            return None;
+        }
+        let hit = pos + direction * t;
+
+        let (along, hit_direction) = if faces_x {
+            let hit_direction = if direction.x > 0.0 {
+                Direction::East
+            } else {
+                Direction::West
+            };
+            (hit.y - cell.y, hit_direction)
+        } else {
+            let hit_direction = if direction.y > 0.0 {
+                Direction::North
+            } else {
+                Direction::South
+            };
+            (hit.x - cell.x, hit_direction)
+        };
+        if !(0.0..1.0).contains(&along) || !door.blocks(along) {
+            return None;
+        }
+
+        // the texture slides with the slab
+        let slid = if faces_x {
+            hit.with_y(hit.y - door.open_amount())
+        } else {
+            hit.with_x(hit.x - door.open_amount())
+        };
+        Some(HitInfo {
+            distance: t * direction.magnitude(),
+            perp_distance: t,
+            hit,
+            hit_object: self.tile(x, y),
+            cell: [x, y],
+            hit_direction,
+            wall_offset: wall_offset(slid, hit_direction),
+        })
+    }
+}
+
+/// Calculates [HitInfo::wall_offset] for a hit at `hit` by a ray moving in `direction`.
+/// The offset is flipped for faces we're looking at in negative direction so textures aren't mirrored.
+fn wall_offset(hit: Vec2d, direction: Direction) -> f64 {
+    let fract = |v: f64| v - v.floor();
+    match direction {
+        Direction::East => fract(hit.y),
+        Direction::West => 1.0 - fract(hit.y),
+        Direction::North => 1.0 - fract(hit.x),
+        Direction::South => fract(hit.x),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use crate::angle::Angle;
+    use crate::consts::world::DOOR_SPEED;
+    use crate::direction::Direction;
+    use crate::player::Player;
+    use crate::ray::{Ray, Raycastable};
+    use crate::vec2d::Vec2d;
+    use crate::world::entity::Entity;
+    use crate::world::raycast::{CellEntry, GridTraversal, RayQuery, WorldHit};
+    use crate::world::tile::Tile::{self, Air, Door, Glass, Grate, Stone, Wall};
+    use crate::world::World;
+
+    const SIZE: usize = 8;
+
+    /// Casts the ray by calculating where it crosses each of the cell boundaries of the world on its own,
+    /// then walking through the crossings ordered by the ray parameter.
+    /// Like [GridTraversal], the x boundary is crossed first when the ray goes exactly through a corner.
+    /// Returns the solid cell which is entered first, the direction it's entered in and the ray parameter.
+    fn reference_cast(world: &World, pos: Vec2d, direction: Vec2d) -> ([usize; 2], Direction, f64) {
+        // the ray parameter, the axis, the coordinate of the entered cell on the axis and the direction
+        let mut crossings = Vec::new();
+        for boundary in 0..=SIZE as i64 {
+            let axes = [
+                (pos.x, direction.x, Direction::East, Direction::West),
+                (pos.y, direction.y, Direction::North, Direction::South),
+            ];
+            for (axis, (pos, direction, positive, negative)) in axes.into_iter().enumerate() {
+                let t = (boundary as f64 - pos) / direction;
+                if direction > 0.0 && t > 0.0 {
+                    crossings.push((t, axis, boundary, positive));
+                } else if direction < 0.0 && t >= 0.0 {
+                    crossings.push((t, axis, boundary - 1, negative));
+                }
+            }
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        let mut cell = [pos.x.floor() as i64, pos.y.floor() as i64];
+        for (t, axis, coordinate, direction) in crossings {
+            cell[axis] = coordinate;
+            let [x, y] = cell.map(|coordinate| coordinate as usize);
+            if world.tile(x, y).unwrap().is_solid() {
+                return ([x, y], direction, t);
+            }
+        }
+        unreachable!("the world has a solid border")
+    }
+
+    /// Random worlds with a solid border and a position in one of their air tiles
+    fn world_and_pos() -> impl Strategy<Value = (World, Vec2d)> {
+        let tiles = proptest::collection::vec(prop::bool::weighted(0.3), SIZE * SIZE);
+        let pos = (1.0..SIZE as f64 - 1.0, 1.0..SIZE as f64 - 1.0);
+        (tiles, pos).prop_filter_map("starts in a wall", |(solid, (x, y))| {
+            let tiles = solid
+                .iter()
+                .enumerate()
+                .map(|(i, &solid)| {
+                    let (x, y) = (i % SIZE, i / SIZE);
+                    let border = x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1;
+                    if solid || border {
+                        Wall
+                    } else {
+                        Air
+                    }
+                })
+                .collect();
+            let world = World::from_tiles(SIZE, SIZE, tiles)?;
+            let pos = Vec2d { x, y };
+            (!world.is_solid_at(pos)).then_some((world, pos))
+        })
+    }
+
+    /// Any direction, with extra weight on the axis aligned and diagonal ones, not normalized
+    fn direction() -> impl Strategy<Value = Vec2d> {
+        let any = (0.0..360.0).prop_map(|degree| Angle::from_degree(degree).vec());
+        let exact = (-1..=1i32, -1..=1i32)
+            .prop_filter("not zero", |&(x, y)| x != 0 || y != 0)
+            .prop_map(|(x, y)| Vec2d {
+                x: x as f64,
+                y: y as f64,
+            });
+        (prop_oneof![any, exact], 0.1..10.0).prop_map(|(direction, length)| direction * length)
+    }
+
+    /// Rays from the center of a cell with whole numbers as direction, they go exactly through corners of the grid
+    fn corner_ray() -> impl Strategy<Value = (World, Vec2d, Vec2d)> {
+        let direction = (-3..=3i32, -3..=3i32)
+            .prop_filter("not zero", |&(x, y)| x != 0 || y != 0)
+            .prop_map(|(x, y)| Vec2d {
+                x: x as f64,
+                y: y as f64,
+            });
+        (world_and_pos(), direction)
+            .prop_map(|((world, pos), direction)| (world, pos.floor() + 0.5, direction))
+    }
+
+    /// Checks the hit of the ray against [reference_cast] and that the hit information is consistent
+    fn check_cast(world: &World, pos: Vec2d, direction: Vec2d) -> Result<(), TestCaseError> {
+        let hit = world.cast(&RayQuery::new(pos, direction)).unwrap();
+        let (cell, hit_direction, t) = reference_cast(world, pos, direction);
+        prop_assert_eq!(hit.cell, cell);
+        prop_assert_eq!(hit.hit_direction, hit_direction);
+        prop_assert_eq!(hit.perp_distance, t);
+
+        prop_assert!((hit.distance - hit.perp_distance * direction.magnitude()).abs() < 1e-9);
+        prop_assert!((pos + direction * hit.perp_distance - hit.hit).magnitude() < 1e-9);
+        // the hit is on the face of the cell the ray enters through
+        let face = Vec2d {
+            x: hit.cell[0] as f64 + 0.5,
+            y: hit.cell[1] as f64 + 0.5,
+        } + hit.normal() * 0.5;
+        let along_face = hit.hit - face;
+        prop_assert!(along_face.dot(hit.normal()).abs() < 1e-9);
+        prop_assert!((0.0..=1.0).contains(&hit.wall_offset));
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_cast_matches_reference((world, pos) in world_and_pos(), direction in direction()) {
+            check_cast(&world, pos, direction)?;
+        }
+
+        #[test]
+        fn test_corner_cast_matches_reference((world, pos, direction) in corner_ray()) {
+            check_cast(&world, pos, direction)?;
+        }
+    }
+
+    #[test]
+    fn test_cast_through_corner() {
+        let world = World::from_rows(&[
+            [Wall, Wall, Wall, Wall],
+            [Wall, Air, Wall, Wall],
+            [Wall, Air, Air, Wall],
+            [Wall, Wall, Wall, Wall],
+        ])
+        .unwrap();
+        // exactly through the corner between the wall on the right and the air below, x is stepped first
+        let hit = world
+            .cast(&RayQuery::new(
+                Vec2d { x: 1.5, y: 1.5 },
+                Vec2d { x: 1.0, y: 1.0 },
+            ))
+            .unwrap();
+        assert_eq!(hit.cell, [2, 1]);
+        assert_eq!(hit.hit_direction, Direction::East);
+        assert_eq!(hit.hit, Vec2d { x: 2.0, y: 2.0 });
+    }
+
+    #[test]
+    fn test_grid_traversal() {
+        let pos = Vec2d { x: 0.5, y: 0.5 };
+        let cells = |direction| {
+            GridTraversal::new(pos, direction)
+                .unwrap()
+                .take(4)
+                .map(|entry: CellEntry| entry.cell)
+                .collect::<Vec<_>>()
+        };
+        // axis aligned rays never change the other axis
+        assert_eq!(
+            cells(Vec2d { x: 0.0, y: -2.0 }),
+            [[0, 0], [0, -1], [0, -2], [0, -3]]
+        );
+        // through the corners, x is stepped first
+        assert_eq!(
+            cells(Vec2d { x: 1.0, y: 1.0 }),
+            [[0, 0], [1, 0], [1, 1], [2, 1]]
+        );
+
+        let mut traversal = GridTraversal::new(pos, Vec2d { x: -0.5, y: 0.0 }).unwrap();
+        assert_eq!(traversal.next().unwrap().direction, None);
+        assert_eq!(
+            traversal.next(),
+            Some(CellEntry {
+                cell: [-1, 0],
+                t: 1.0,
+                direction: Some(Direction::West)
+            })
+        );
+        assert!(GridTraversal::new(pos, Vec2d::default()).is_none());
+        assert!(GridTraversal::new(
+            pos,
+            Vec2d {
+                x: f64::NAN,
+                y: 1.0
+            }
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_cast_leaves_world() {
+        let world = World::from_rows(&[[Air, Air], [Air, Tile::Wall]]).unwrap();
+        let pos = Vec2d { x: 0.5, y: 0.5 };
+        assert_eq!(
+            world.cast(&RayQuery::new(pos, Vec2d { x: -1.0, y: 0.0 })),
+            None
+        );
+        assert_eq!(
+            world.cast(&RayQuery::new(pos, Vec2d { x: 1.0, y: 0.0 })),
+            None
+        );
+        let hit = world
+            .cast(&RayQuery::new(pos, Vec2d { x: 1.0, y: 1.0 }))
+            .unwrap();
+        assert_eq!(hit.cell, [1, 1]);
+    }
+
+    #[test]
+    fn test_ray_query() {
+        let world = World::from_rows(&[
+            [Wall, Wall, Wall, Wall, Wall, Wall],
+            [Wall, Air, Stone, Air, Air, Wall],
+            [Wall, Wall, Wall, Wall, Wall, Wall],
+        ])
+        .unwrap();
+        let east = Vec2d { x: 1.0, y: 0.0 };
+        let query = RayQuery::new(Vec2d { x: 1.5, y: 1.5 }, east);
+
+        assert_eq!(world.cast(&query).unwrap().cell, [2, 1]);
+        assert_eq!(world.cast(&query.max_distance(0.4)), None);
+        assert_eq!(world.cast(&query.max_distance(0.5)).unwrap().cell, [2, 1]);
+
+        // only walls block, the ray passes through the stone
+        let walls = query.blocking(|tile: &Tile| *tile == Wall);
+        let hit = world.cast(&walls).unwrap();
+        assert_eq!(hit.cell, [5, 1]);
+        assert_eq!(hit.distance, 3.5);
+        assert_eq!(world.cast(&walls.max_distance(3.0)), None);
+
+        // starting inside of the stone
+        let inside = RayQuery::new(Vec2d { x: 2.25, y: 1.5 }, east);
+        let hit = world.cast(&inside).unwrap();
+        assert_eq!(hit.cell, [2, 1]);
+        assert_eq!(hit.distance, 0.0);
+        assert_eq!(hit.hit_direction, Direction::East);
+        let hit = world.cast(&inside.ignore_start(true)).unwrap();
+        assert_eq!(hit.cell, [5, 1]);
+        assert_eq!(hit.distance, 2.75);
+    }
+
+    #[test]
+    fn test_cast_all() {
+        let world = World::from_rows(&[[Air, Glass, Air, Grate, Stone, Air, Wall]]).unwrap();
+        let query = RayQuery::new(Vec2d { x: 0.5, y: 0.5 }, Vec2d { x: 1.0, y: 0.0 });
+
+        let hits: Vec<_> = world
+            .cast_all(&query)
+            .map(|hit| (hit.hit_object.copied(), hit.distance))
+            .collect();
+        assert_eq!(
+            hits,
+            [
+                (Some(Glass), 0.5),
+                (Some(Grate), 2.5),
+                (Some(Stone), 3.5),
+                (Some(Wall), 5.5)
+            ]
+        );
+        // everything up to the first tile which can't be seen through
+        let visible = world
+            .cast_all(&query)
+            .position(|hit| !hit.hit_object.unwrap().is_transparent());
+        assert_eq!(visible, Some(2));
+        assert_eq!(world.cast_all(&query.max_distance(3.0)).count(), 2);
+        assert_eq!(world.cast(&query).unwrap().hit_object, Some(&Glass));
+    }
+
+    #[test]
+    fn test_cast_objects() {
+        let mut world = World::from_rows(&[[Air, Air, Air, Air, Air, Wall]]).unwrap();
+        let barrel = world.add_entity(Entity::prop("barrel", Vec2d { x: 3.5, y: 0.5 }));
+        let mut lamp = Entity::prop("lamp", Vec2d { x: 2.5, y: 0.5 });
+        lamp.collider = None;
+        world.add_entity(lamp);
+        let query = RayQuery::new(Vec2d { x: 0.5, y: 0.5 }, Vec2d { x: 1.0, y: 0.0 });
+
+        let hit = world.cast_objects(&query).unwrap();
+        assert_eq!(hit.entity_id(), Some(barrel));
+        assert!((hit.distance() - 2.7).abs() < 1e-9);
+        assert!((hit.normal() - Vec2d { x: -1.0, y: 0.0 }).magnitude() < 1e-9);
+
+        let WorldHit::Tile(hit) = world.cast_objects(&query.ignore_entity(barrel)).unwrap() else {
+            panic!("the ray passes the ignored barrel");
+        };
+        assert_eq!(hit.cell, [5, 0]);
+        assert!(world.cast_objects(&query.max_distance(2.5)).is_none());
+
+        // walls in front of the entity hide it
+        world.set_tile(2, 0, Wall);
+        assert_eq!(world.cast_objects(&query).unwrap().entity_id(), None);
+    }
+
+    #[test]
+    fn test_wall_offset() {
+        let world = World::from_rows(&[
+            [Wall, Wall, Wall, Wall],
+            [Wall, Air, Air, Wall],
+            [Wall, Wall, Wall, Wall],
+        ])
+        .unwrap();
+
+        let east = world
+            .cast_ray(Ray {
+                pos: Vec2d { x: 1.5, y: 1.25 },
+                angle: Angle::from_degree(0.0),
+            })
+            .unwrap();
+        assert_eq!(east.hit_direction, Direction::East);
+        assert!((east.wall_offset - 0.25).abs() < 1e-6);
+
+        let west = world
+            .cast_ray(Ray {
+                pos: Vec2d { x: 2.5, y: 1.25 },
+                angle: Angle::from_degree(180.0),
+            })
+            .unwrap();
+        assert_eq!(west.hit_direction, Direction::West);
+        assert!((west.wall_offset - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_door_slides_open() {
+        let mut world = World::from_rows(&[
+            [Wall, Wall, Wall, Wall, Wall],
+            [Air, Air, Door, Air, Wall],
+            [Wall, Wall, Wall, Wall, Wall],
+        ])
+        .unwrap();
+        let ray = |y| Ray {
+            pos: Vec2d { x: 0.5, y },
+            angle: Angle::from_degree(0.0),
+        };
+
+        // the closed door is hit in the middle of its tile
+        let hit = world.cast_ray(ray(1.25)).unwrap();
+        assert_eq!(hit.hit_object, Some(&Door));
+        assert_eq!(hit.cell, [2, 1]);
+        assert!((hit.distance - 2.0).abs() < 1e-6);
+        assert!((hit.wall_offset - 0.25).abs() < 1e-6);
+
+        world.door_mut(2, 1).unwrap().toggle();
+        world.update(0.5 / DOOR_SPEED, &Player::default());
+        let open_amount = world.door(2, 1).unwrap().open_amount();
+
+        // the part which slid into the wall lets rays pass, the rest moved with the slab
+        let passing = world.cast_ray(ray(1.0 + 0.5 * open_amount)).unwrap();
+        assert_eq!(passing.hit_object, Some(&Wall));
+        let hit = world.cast_ray(ray(1.0 + open_amount + 0.25)).unwrap();
+        assert_eq!(hit.hit_object, Some(&Door));
+        assert!((hit.wall_offset - 0.25).abs() < 1e-6);
+    }
+}
@@ -1,231 +1,338 @@
 use crate::angle::Angle;
-use crate::consts::render::{
-    CHANGE_ANGLE_PER_RAY, CHANGE_SCREEN_X_PER_RAY, FOV, MINIMAP_HEIGHT, MINIMAP_WIDTH,
-    RAY_SCREEN_HEIGHT, RAY_SCREEN_WIDTH,
-};
-use crate::direction::Direction;
+use crate::camera::Camera;
+use crate::canvas::{Canvas, Rect};
+use crate::color::{Color, ColorLike, BLACK, BLUE, RED, WHITE};
+use crate::consts::render::{CEILING_COLOR, FAR_PLANE, MINIMAP_HEIGHT, MINIMAP_WIDTH};
+use crate::consts::weapon::WEAPON_SCREEN_SIZE;
 use crate::player::Player;
-use crate::ray::{HitInfo, Ray, Raycastable};
+use crate::ray::HitInfo;
+use crate::texture::Texture;
 use crate::vec2d::Vec2d;
+use crate::world::raycast::RayQuery;
 use crate::world::tile::Tile;
 use crate::world::World;
-use conv::ConvAsUtil;
-use graphics::color::{BLUE, RED, WHITE};
-use graphics::types::Color;
-use graphics::{Context, Graphics, Line, Rectangle};
 
 impl World {
-    pub fn render<G>(&self, g: &mut G, ctx: &mut Context, player: &Player)
+    /// Renders the background, the world, the weapon of the player and the minimap
+    pub fn render_scene<C>(&self, canvas: &mut C, player: &Player)
     where
-        G: Graphics,
+        C: Canvas,
     {
-        let ray_screen_width = RAY_SCREEN_WIDTH;
-        let ray_screen_height = RAY_SCREEN_HEIGHT;
-        let mut ray_screen_x = 0.0;
-        let mut degree = Angle::from_degree(0.0);
-        while degree <= FOV {
-            degree = Angle::from_degree(degree.degree() + CHANGE_ANGLE_PER_RAY.degree());
-            let ray_angle = degree + player.direction;
-            ray_screen_x += CHANGE_SCREEN_X_PER_RAY;
+        // everything fades to black towards the far plane
+        let [width, height] = canvas.size();
+        canvas.fill_rect([0.0, 0.0, width, height], BLACK);
+        self.render(canvas, player);
+        self.render_weapon(canvas, player);
+        self.render_mini_map(canvas, player);
+    }
 
-            let ray = Ray {
-                pos: player.pos,
-                angle: ray_angle - Angle::from_degree(FOV.degree() / 2.0),
-            };
-            if let Some(hit_info) = self.cast_ray(ray) {
+    /// Draws the current frame of the weapon sprite at the bottom of the screen, see [Weapon::animation_frame](crate::weapon::Weapon::animation_frame)
+    pub fn render_weapon<C>(&self, canvas: &mut C, player: &Player)
+    where
+        C: Canvas,
+    {
+        let weapon = &player.weapon;
+        let Some(frames) = self.textures().sprite(weapon.stats().sprite) else {
+            return;
+        };
+        let frame = &frames[weapon.animation_frame(frames.len())];
+        let [width, height] = canvas.size();
+        let size = height * WEAPON_SCREEN_SIZE;
+        let left = (width - size) / 2.0;
+        // it only bobs down, so its bottom doesn't come into view
+        let top = height - size + player.bob_offset().abs() * height;
+        let columns = (left.floor().max(0.0) as usize)..((left + size).ceil().min(width) as usize);
+        for column in columns {
+            let u = (column as f64 + 0.5 - left) / size;
+            if !(0.0..1.0).contains(&u) {
+                continue;
+            }
+            render_texture_column(
+                frame,
+                frame.column(u),
+                [column as f64, top, 1.0, size],
+                height,
+                WHITE,
+                canvas,
+            );
+        }
+    }
+
+    pub fn render<C>(&self, canvas: &mut C, player: &Player)
+    where
+        C: Canvas,
+    {
+        let camera = Camera::new(player, canvas.size());
+        self.render_floor_and_ceiling(&camera, canvas);
+        let sprites = self.project_sprites(&camera);
+
+        let mut hits: Vec<HitInfo<'_, Tile>> = Vec::new();
+        for column in 0..camera.screen_width as usize {
+            let screen_x = column as f64;
+            // cast through the center of the column
+            let direction = camera.ray_direction(screen_x + 0.5);
+            // walls behind the far plane aren't drawn anyway
+            let query = RayQuery::new(camera.pos, direction)
+                .max_distance(FAR_PLANE * direction.magnitude());
+            hits.clear();
+            for hit_info in self.cast_all(&query) {
+                let opaque = hit_info
+                    .hit_object
+                    .is_none_or(|tile| !tile.is_transparent());
+                // the face between two transparent tiles of the same kind is inside of one pane, so it isn't drawn
+                let shared_face = hits.last().is_some_and(|previous| {
+                    let [x, y] = previous.cell;
+                    let distance = x.abs_diff(hit_info.cell[0]) + y.abs_diff(hit_info.cell[1]);
+                    previous.hit_object == hit_info.hit_object && distance == 1
+                });
+                if !shared_face || opaque {
+                    hits.push(hit_info);
+                }
+                if opaque {
+                    break;
+                }
+            }
+
+            // sprites behind the opaque tile are hidden
+            let wall_depth = hits
+                .last()
+                .filter(|hit| hit.hit_object.is_none_or(|tile| !tile.is_transparent()))
+                .map_or(f64::INFINITY, |hit| hit.perp_distance);
+            let mut sprites = sprites
+                .iter()
+                .filter(|sprite| sprite.depth < wall_depth)
+                .peekable();
+            // back to front, so the tiles and sprites in front are drawn over the ones behind them
+            for hit_info in hits.iter().rev() {
+                while let Some(sprite) =
+                    sprites.next_if(|sprite| sprite.depth >= hit_info.perp_distance)
+                {
+                    sprite.render_column(&camera, column, canvas);
+                }
                 if let Some(tile) = hit_info.hit_object {
                     tile.render_screen_slice(
-                        ray_screen_x,
-                        ray_screen_width,
-                        ray_screen_height,
-                        &hit_info,
-                        g,
-                        ctx,
+                        &camera,
+                        screen_x,
+                        hit_info,
+                        self.textures().tile(*tile),
+                        canvas,
                     );
-                    // // Debug for rendering rays on the minimap
-                    // let tile_width = MINIMAP_WIDTH / World::WIDTH as f64;
-                    // let tile_height = MINIMAP_HEIGHT / World::HEIGHT as f64;
-                    // let player_screen_pos = Vec2d {
-                    //     x: player.pos.x * tile_width,
-                    //     y: player.pos.y * tile_height,
-                    // };
-                    // g.line(
-                    //     &Line::new(WHITE, 0.5),
-                    //     [
-                    //         player_screen_pos.x,
-                    //         player_screen_pos.y,
-                    //         hit_info.hit.x * tile_width,
-                    //         hit_info.hit.y * tile_height,
-                    //     ],
-                    //     &ctx.draw_state,
-                    //     ctx.transform,
-                    // );
                 }
             }
+            for sprite in sprites {
+                sprite.render_column(&camera, column, canvas);
+            }
         }
     }
 }
 
-impl Raycastable for World {
-    type HitObject = Tile;
-
-    fn cast_ray(&self, ray: Ray) -> Option<HitInfo<'_, Self::HitObject>> {
-        self.cast_efficient(ray)
-    }
-}
-
 impl World {
-    fn cast_simple(&self, ray: Ray) -> Option<HitInfo<'_, Tile>> {
-        const OFFSET: f64 = 0.1;
-        let mut pos = ray.pos;
-        let mut dist = 0.0;
-        let off = ray.angle.normalize().vec().with_magnitude(OFFSET);
-        let dist_off = off.magnitude();
-
-        while let Some(tile) = self.tile_vec(pos) {
-            if tile.is_solid() {
-                return Some(HitInfo {
-                    distance: dist,
-                    hit: pos,
-                    hit_object: Some(tile),
-                    hit_direction: {
-                        let next_boundary_x = pos.x.floor() + if off.x > 0.0 { 1.0 } else { 0.0 };
-                        let next_boundary_y = pos.y.floor() + if off.y > 0.0 { 1.0 } else { 0.0 };
+    /// Casts the floor row by row below the horizon and mirrors it for the ceiling above the horizon.
+    /// The walls are drawn over it afterwards.
+    fn render_floor_and_ceiling<C>(&self, camera: &Camera, canvas: &mut C)
+    where
+        C: Canvas,
+    {
+        let width = camera.screen_width as usize;
+        let horizon = camera.horizon();
+        // the camera is half a tile above the floor and below the ceiling
+        let camera_height = 0.5;
+        let left = camera.ray_direction(0.0);
+        let right = camera.ray_direction(camera.screen_width);
 
-                        let boundary_dist = (pos
-                            - Vec2d {
-                                x: next_boundary_x,
-                                y: next_boundary_y,
-                            })
-                        .abs();
+        let mut floor_row = Vec::with_capacity(width);
+        let mut ceiling_row = Vec::with_capacity(width);
+        // the floor reaches the bottom of the screen and the mirrored ceiling the top,
+        // when the horizon isn't in the middle one of them reaches further
+        let last_row = camera.screen_height.max(2.0 * horizon).ceil() as usize;
+        for row in horizon.ceil() as usize..last_row {
+            let row_offset = row as f64 + 0.5 - horizon;
+            let row_distance = camera_height * camera.focal_length() / row_offset;
+            if row_distance > FAR_PLANE {
+                continue;
+            }
+            let shade = Color::new_single(1.0 - (row_distance / FAR_PLANE) as f32);
 
-                        let dist_x_bigger_y = boundary_dist.x > boundary_dist.y;
-                        let x_positive = off.x > 0.0;
-                        match (dist_x_bigger_y, x_positive) {
-                            (true, true) => Direction::East,
-                            (true, false) => Direction::West,
-                            (false, true) => Direction::South,
-                            (false, false) => Direction::North,
-                        }
-                    },
+            let step = (right - left) * (row_distance / camera.screen_width);
+            let mut pos = camera.pos + left * row_distance + step * 0.5;
+            floor_row.clear();
+            ceiling_row.clear();
+            for _ in 0..width {
+                let tile = self.tile_vec(pos).copied();
+                let floor = tile.and_then(|tile| match self.textures().floor(tile) {
+                    Some(texture) => Some(texture.sample(pos.x, pos.y)),
+                    None => tile.floor_color(),
                 });
+                // every tile with a floor has a ceiling
+                let ceiling = tile
+                    .filter(|tile| tile.floor_color().is_some())
+                    .map(|_| match self.textures().ceiling() {
+                        Some(texture) => texture.sample(pos.x, pos.y),
+                        None => CEILING_COLOR,
+                    });
+                floor_row.push(floor.map(|color| color.mul_other(shade)));
+                ceiling_row.push(ceiling.map(|color| color.mul_other(shade)));
+                pos += step;
             }
-            pos += off;
-            dist += dist_off;
+
+            render_row(row as f64, &floor_row, canvas);
+            render_row(2.0 * horizon - row as f64 - 1.0, &ceiling_row, canvas);
         }
-        None
     }
+}
 
-    fn cast_efficient(&self, ray: Ray) -> Option<HitInfo<'_, Tile>> {
-        // casting on x axis:
-        let x_hit = {
-            let direction = ray.angle.vec();
-            let direction_x = direction.x / direction.x.abs();
-            let next_boundary_x = ray.pos.x.floor() + if direction.x > 0.0 { 1.0 } else { 0.0 };
-            let per_x_tile_offset = direction / direction.x.abs();
+/// A sprite projected onto the screen, see [World::project_sprites]
+struct ProjectedSprite<'a> {
+    /// The distance along the direction of the camera
+    depth: f64,
+    left: f64,
+    top: f64,
+    screen_size: f64,
+    shade: Color,
+    frame: &'a Texture,
+}
 
-            let next_boundary_dist_x = (ray.pos.x - next_boundary_x).abs();
-            let mut next_boundary_hit =
-                ray.pos + direction.scale_x(next_boundary_dist_x) * direction_x;
-            // because of precision errors we need to sub a tiny value when going negative
-            if direction_x < 0.0 {
-                next_boundary_hit.x -= 0.0001;
+impl ProjectedSprite<'_> {
+    /// Draws the column of the sprite at the column of the screen, if the sprite covers it
+    fn render_column<C>(&self, camera: &Camera, column: usize, canvas: &mut C)
+    where
+        C: Canvas,
+    {
+        let u = (column as f64 + 0.5 - self.left) / self.screen_size;
+        if !(0.0..1.0).contains(&u) {
+            return;
+        }
+        render_texture_column(
+            self.frame,
+            self.frame.column(u),
+            [column as f64, self.top, 1.0, self.screen_size],
+            camera.screen_height,
+            self.shade,
+            canvas,
+        );
+    }
+}
+
+impl World {
+    /// Projects the sprites of the entities in front of the camera onto the screen, ordered from back to front.
+    /// They are drawn column by column together with the tiles, so they can be behind transparent tiles.
+    fn project_sprites(&self, camera: &Camera) -> Vec<ProjectedSprite<'_>> {
+        let plane_length_squared = camera.plane.dot(camera.plane);
+        let mut sprites = Vec::new();
+        for (_, entity) in self.entities() {
+            let Some(sprite) = &entity.sprite else {
+                continue;
+            };
+            let Some(frames) = self.textures().sprite(&sprite.name) else {
+                continue;
+            };
+            // the position in camera space, depth is along the direction and side along the plane
+            let relative = entity.pos - camera.pos;
+            let depth = relative.dot(camera.direction);
+            let side = relative.dot(camera.plane) / plane_length_squared;
+            if depth <= SPRITE_NEAR_PLANE || depth >= FAR_PLANE {
+                continue;
             }
 
-            let mut result = None;
-            while let Some(tile) = self.tile_vec(next_boundary_hit) {
-                if tile.is_solid() {
-                    result = Some(HitInfo {
-                        distance: ray.pos.distance_to(next_boundary_hit),
-                        hit: next_boundary_hit,
-                        hit_object: Some(tile),
-                        hit_direction: if direction_x > 0.0 {
-                            Direction::East
-                        } else {
-                            Direction::West
-                        },
-                    });
-                    break;
-                }
+            let screen_x = camera.screen_width / 2.0 * (1.0 + side / depth);
+            let tile_height = camera.projected_height(depth);
+            let screen_size = tile_height * sprite.size;
+            // the sprite stands on the floor
+            let bottom = camera.horizon() + tile_height / 2.0;
+            sprites.push(ProjectedSprite {
+                depth,
+                left: screen_x - screen_size / 2.0,
+                top: bottom - screen_size,
+                screen_size,
+                shade: Color::new_single(1.0 - (depth / FAR_PLANE) as f32),
+                frame: sprite_frame(frames, entity.facing, -relative),
+            });
+        }
+        sprites.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+        sprites
+    }
+}
 
-                next_boundary_hit += per_x_tile_offset;
-            }
-            result
-        };
-        // casting on y axis:
-        let y_hit = {
-            let direction = ray.angle.vec();
-            let direction_y = direction.y / direction.y.abs();
-            let next_boundary_y = ray.pos.y.floor() + if direction.y > 0.0 { 1.0 } else { 0.0 };
-            let per_y_tile_offset = direction / direction.y.abs();
+/// Sprites closer than this to the camera plane aren't drawn
+const SPRITE_NEAR_PLANE: f64 = 0.1;
 
-            let next_boundary_dist_y = (ray.pos.y - next_boundary_y).abs();
-            let mut next_boundary_hit =
-                ray.pos + direction.scale_y(next_boundary_dist_y) * direction_y;
-            // because of precision errors we need to sub a tiny value when going negative
-            if direction_y < 0.0 {
-                next_boundary_hit.y -= 0.0001;
-            }
+/// Picks the frame for looking at an entity from `to_viewer`, the frames go around the entity in the direction of
+/// increasing angles starting with the frame seen when looking at the entity from the front
+fn sprite_frame(frames: &[Texture], facing: Angle, to_viewer: Vec2d) -> &Texture {
+    let view_angle = Angle::from_vec(to_viewer);
+    let relative = (view_angle - facing).normalize().degree();
+    let frame_angle = 360.0 / frames.len() as f64;
+    let index = (relative / frame_angle).round() as usize % frames.len();
+    &frames[index]
+}
 
-            let mut result = None;
-            while let Some(tile) = self.tile_vec(next_boundary_hit) {
-                if tile.is_solid() {
-                    result = Some(HitInfo {
-                        distance: ray.pos.distance_to(next_boundary_hit),
-                        hit: next_boundary_hit,
-                        hit_object: Some(tile),
-                        hit_direction: if direction_y > 0.0 {
-                            Direction::North
-                        } else {
-                            Direction::South
-                        },
-                    });
-                    break;
-                }
+/// Draws a column of the texture stretched over `rect`, clipped to the screen.
+/// Every run of pixels with the same color is drawn as one rectangle, fully transparent runs are skipped.
+pub(crate) fn render_texture_column<C>(
+    texture: &Texture,
+    column: u32,
+    rect: Rect,
+    max_height: f64,
+    shade: Color,
+    canvas: &mut C,
+) where
+    C: Canvas,
+{
+    let [x, top, width, height] = rect;
+    let texel_height = height / texture.height() as f64;
+    let mut run_start = 0;
+    for texel_y in 1..=texture.height() {
+        let color = texture.pixel(column, run_start);
+        if texel_y < texture.height() && texture.pixel(column, texel_y) == color {
+            continue;
+        }
 
-                next_boundary_hit += per_y_tile_offset;
-            }
-            result
-        };
+        let run_top = (top + run_start as f64 * texel_height).max(0.0);
+        let run_bottom = (top + texel_y as f64 * texel_height).min(max_height);
+        if run_bottom > run_top && color.a() > 0.0 {
+            canvas.fill_rect(
+                [x, run_top, width, run_bottom - run_top],
+                color.mul_other(shade),
+            );
+        }
+        run_start = texel_y;
+    }
+}
 
-        match (x_hit, y_hit) {
-            (Some(x_hit), Some(y_hit)) => {
-                if x_hit.distance < y_hit.distance {
-                    Some(x_hit)
-                } else {
-                    Some(y_hit)
-                }
-            }
-            (Some(x_hit), None) => Some(x_hit),
-            (None, Some(y_hit)) => Some(y_hit),
-            (None, None) => None,
+/// Draws a row of pixels as one rectangle per run of the same color, [None] is skipped
+fn render_row<C>(y: f64, colors: &[Option<Color>], canvas: &mut C)
+where
+    C: Canvas,
+{
+    let mut run_start = 0;
+    for x in 1..=colors.len() {
+        if x < colors.len() && colors[x] == colors[run_start] {
+            continue;
         }
+        if let Some(color) = colors[run_start] {
+            canvas.fill_rect([run_start as f64, y, (x - run_start) as f64, 1.0], color);
+        }
+        run_start = x;
     }
 }
 
 impl World {
-    pub fn render_mini_map<G>(&self, g: &mut G, ctx: &mut Context, player: &Player)
+    pub fn render_mini_map<C>(&self, canvas: &mut C, player: &Player)
     where
-        G: Graphics,
+        C: Canvas,
     {
         // Render background
-        g.rectangle(
-            &Rectangle::new(BLUE),
-            [0.0, 0.0, MINIMAP_WIDTH, MINIMAP_HEIGHT],
-            &ctx.draw_state,
-            ctx.transform,
-        );
+        canvas.fill_rect([0.0, 0.0, MINIMAP_WIDTH, MINIMAP_HEIGHT], BLUE);
 
         // Render world
-        let tile_width = MINIMAP_WIDTH / World::WIDTH as f64;
-        let tile_height = MINIMAP_HEIGHT / World::HEIGHT as f64;
-        for x in 0..World::WIDTH {
-            for y in 0..World::HEIGHT {
+        let tile_width = MINIMAP_WIDTH / self.width() as f64;
+        let tile_height = MINIMAP_HEIGHT / self.height() as f64;
+        for x in 0..self.width() {
+            for y in 0..self.height() {
                 let tile = self.tile(x, y);
                 if let Some(tile) = tile {
                     tile.render_minimap(
-                        g,
-                        ctx,
+                        canvas,
                         [
                             (x as f64) * tile_width,
                             (y as f64) * tile_height,
@@ -242,29 +349,26 @@ impl World {
             x: player.pos.x * tile_width,
             y: player.pos.y * tile_height,
         };
-        g.rectangle(
-            &Rectangle::new(WHITE),
+        canvas.fill_rect(
             [
                 player_screen_pos.x,
                 player_screen_pos.y,
                 PLAYER_SIZE,
                 PLAYER_SIZE,
             ],
-            &ctx.draw_state,
-            ctx.transform,
+            WHITE,
         );
         let player_screen_pos = player_screen_pos + (PLAYER_SIZE / 2.0);
         let line_pos = player_screen_pos + player.direction.vec() * 10.0;
-        g.line(
-            &Line::new(RED, 1.0),
+        canvas.line(
             [
                 player_screen_pos.x,
                 player_screen_pos.y,
                 line_pos.x,
                 line_pos.y,
             ],
-            &ctx.draw_state,
-            ctx.transform,
+            2.0,
+            RED,
         );
     }
 }
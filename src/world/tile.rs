@@ -1,96 +1,192 @@
-use std::cmp::max;
-
-use crate::color::ColorLike;
-use crate::consts::render::{FAR_PLANE, NEAR_PLANE};
+use crate::camera::Camera;
+use crate::canvas::{Canvas, Rect};
+use crate::color::{Color, ColorLike, BLACK, GREEN};
+use crate::consts::render::FAR_PLANE;
 use crate::direction::Direction;
 use crate::ray::HitInfo;
-use graphics::color::{BLACK, GREEN};
-use graphics::types::Color;
-use graphics::{Context, Graphics, Rectangle};
+use crate::texture::Texture;
+use crate::world::render::render_texture_column;
+
+const DOOR_MINIMAP_COLOR: Color = [0.5, 0.3, 0.1, 1.0];
+const GLASS_MINIMAP_COLOR: Color = [0.6, 0.8, 1.0, 1.0];
+const GRATE_MINIMAP_COLOR: Color = [0.3, 0.3, 0.3, 1.0];
+/// Drawn for transparent tiles without a texture instead of [GREEN]
+const TRANSPARENT_COLOR: Color = [0.0, 0.5, 0.0, 0.3];
 
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Default, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
 pub enum Tile {
     #[default]
     Air,
     Wall,
+    Brick,
+    Stone,
+    /// A door which opens when interacted with, its state is stored in the world, see [World::door](crate::world::World::door)
+    Door,
+    /// A window pane, solid but transparent
+    Glass,
+    /// Bars with gaps in between, solid but transparent
+    Grate,
 }
 
 impl Tile {
+    pub const ALL: [Tile; 7] = [
+        Tile::Air,
+        Tile::Wall,
+        Tile::Brick,
+        Tile::Stone,
+        Tile::Door,
+        Tile::Glass,
+        Tile::Grate,
+    ];
+
+    /// Doors are solid even though they can be opened, use [World::is_solid_at](crate::world::World::is_solid_at)
+    /// to check for collisions with the part of a door which isn't open.
     pub fn is_solid(&self) -> bool {
         match self {
             Tile::Air => false,
-            Tile::Wall => true,
+            Tile::Wall | Tile::Brick | Tile::Stone | Tile::Door | Tile::Glass | Tile::Grate => true,
+        }
+    }
+
+    /// Whether the tiles behind this one can be seen through it.
+    /// The texture of a transparent tile is drawn over what's behind it, its transparent pixels let that show through.
+    pub fn is_transparent(&self) -> bool {
+        match self {
+            Tile::Air | Tile::Glass | Tile::Grate => true,
+            Tile::Wall | Tile::Brick | Tile::Stone | Tile::Door => false,
+        }
+    }
+
+    /// The name of the texture file for this tile, see [Textures::load_dir](crate::texture::Textures::load_dir)
+    pub fn texture_name(self) -> Option<&'static str> {
+        match self {
+            Tile::Air => None,
+            Tile::Wall => Some("wall"),
+            Tile::Brick => Some("brick"),
+            Tile::Stone => Some("stone"),
+            Tile::Door => Some("door"),
+            Tile::Glass => Some("glass"),
+            Tile::Grate => Some("grate"),
+        }
+    }
+
+    /// The name of the floor texture file for this tile, opaque solid tiles don't have a floor
+    pub fn floor_texture_name(self) -> Option<&'static str> {
+        match self {
+            Tile::Air | Tile::Door | Tile::Glass | Tile::Grate => Some("floor"),
+            Tile::Wall | Tile::Brick | Tile::Stone => None,
+        }
+    }
+
+    /// The color of the floor when there is no floor texture
+    pub fn floor_color(self) -> Option<Color> {
+        match self {
+            Tile::Air | Tile::Door | Tile::Glass | Tile::Grate => Some([0.4, 0.4, 0.4, 1.0]),
+            Tile::Wall | Tile::Brick | Tile::Stone => None,
+        }
+    }
+
+    /// The character representing this tile in a map file
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Air => '.',
+            Tile::Wall => '#',
+            Tile::Brick => 'B',
+            Tile::Stone => 'S',
+            Tile::Door => 'D',
+            Tile::Glass => 'G',
+            Tile::Grate => 'H',
+        }
+    }
+
+    /// Parses a character from a map file, see [Tile::to_char]
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '.' => Some(Tile::Air),
+            '#' => Some(Tile::Wall),
+            'B' => Some(Tile::Brick),
+            'S' => Some(Tile::Stone),
+            'D' => Some(Tile::Door),
+            'G' => Some(Tile::Glass),
+            'H' => Some(Tile::Grate),
+            _ => None,
         }
     }
 }
 
 impl Tile {
-    pub fn render_screen_slice<G>(
+    /// Renders the one pixel wide column at `x` for a ray cast through that column
+    pub fn render_screen_slice<C>(
         &self,
+        camera: &Camera,
         x: f64,
-        width: f64,
-        max_height: f64,
         hit_info: &HitInfo<Tile>,
-        g: &mut G,
-        ctx: &mut Context,
+        texture: Option<&Texture>,
+        canvas: &mut C,
     ) where
-        G: Graphics,
+        C: Canvas,
     {
-        let distance = ((hit_info.distance - NEAR_PLANE) / FAR_PLANE).max(0.0);
+        let perp_distance = camera.perp_distance(hit_info);
+        // used for shading, 0 is right in front of the camera and 1 is the far plane
+        let distance = (perp_distance / FAR_PLANE).max(0.0);
         if distance > 1.0 {
             return;
         }
-        let screen_y = max_height - (distance * max_height);
-        let screen_height = (max_height * distance) - screen_y;
+        let wall_height = camera.projected_height(perp_distance);
+        let screen_y = camera.horizon() - wall_height / 2.0;
 
-        match self {
-            Tile::Wall => self.render_wall(
-                x,
-                screen_y,
-                width,
-                screen_height,
-                distance,
-                hit_info,
-                g,
-                ctx,
-            ),
-            _ => {}
-        }
+        self.render_wall(
+            [x, screen_y, 1.0, wall_height],
+            camera.screen_height,
+            distance,
+            hit_info,
+            texture,
+            canvas,
+        );
     }
 
-    pub fn render_wall<G>(
+    pub fn render_wall<C>(
         &self,
-        screen_x: f64,
-        screen_y: f64,
-        screen_width: f64,
-        screen_height: f64,
+        rect: Rect,
+        max_height: f64,
         distance: f64,
         hit_info: &HitInfo<Tile>,
-        g: &mut G,
-        ctx: &mut Context,
+        texture: Option<&Texture>,
+        canvas: &mut C,
     ) where
-        G: Graphics,
+        C: Canvas,
     {
-        let rect = [screen_x, screen_y, screen_width, screen_height];
+        let shade = Color::new_single(match hit_info.hit_direction {
+            Direction::North | Direction::South => 0.9,
+            Direction::East | Direction::West => 0.95,
+        })
+        .mul_other(Color::new_single(1.0 - distance as f32));
 
-        let color = GREEN
-            .mul_other(match hit_info.hit_direction {
-                Direction::North | Direction::South => [0.9, 0.9, 0.9, 1.0],
-                Direction::East | Direction::West => [0.95, 0.95, 0.95, 1.0],
-            })
-            .mul_other(Color::new_single(1.0 - distance as f32));
+        let Some(texture) = texture else {
+            let color = if self.is_transparent() {
+                TRANSPARENT_COLOR
+            } else {
+                GREEN
+            };
+            canvas.fill_rect(rect, color.mul_other(shade));
+            return;
+        };
 
-        g.rectangle(&Rectangle::new(color), rect, &ctx.draw_state, ctx.transform);
+        let column = texture.column(hit_info.wall_offset);
+        render_texture_column(texture, column, rect, max_height, shade, canvas);
     }
 
-    pub fn render_minimap<G>(&self, g: &mut G, ctx: &mut Context, rect: graphics::types::Rectangle)
+    pub fn render_minimap<C>(&self, canvas: &mut C, rect: Rect)
     where
-        G: Graphics,
+        C: Canvas,
     {
         match self {
             Tile::Air => {}
-            Tile::Wall => g.rectangle(&Rectangle::new(BLACK), rect, &ctx.draw_state, ctx.transform),
+            Tile::Wall | Tile::Brick | Tile::Stone => canvas.fill_rect(rect, BLACK),
+            Tile::Door => canvas.fill_rect(rect, DOOR_MINIMAP_COLOR),
+            Tile::Glass => canvas.fill_rect(rect, GLASS_MINIMAP_COLOR),
+            Tile::Grate => canvas.fill_rect(rect, GRATE_MINIMAP_COLOR),
         }
     }
 }